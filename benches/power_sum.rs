@@ -0,0 +1,178 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use quack::prelude::*;
+
+const THRESHOLDS: [usize; 3] = [10, 100, 1000];
+const NUM_MISSING: [usize; 3] = [1, 8, 64];
+/// Number of packets sent in each trial.
+const NUM_PACKETS: usize = 1000;
+const SEED: u64 = 0x71ac;
+
+/// Precomputes the modular inverses for the largest benchmarked threshold.
+/// The inverse table is only computed once, so every benchmark calls this
+/// before constructing any quACKs.
+fn setup() {
+    quack::global_config_set_max_power_sum_threshold(*THRESHOLDS.iter().max().unwrap());
+}
+
+/// Returns the deterministic log of sent packets, the quACK of sent packets,
+/// and the quACK of received packets, where the receiver is missing the first
+/// `num_missing` packets in the log.
+fn gen_quacks(
+    threshold: usize,
+    num_missing: usize,
+) -> (Vec<u32>, PowerSumQuackU32, PowerSumQuackU32) {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let log = (0..NUM_PACKETS).map(|_| rng.gen()).collect::<Vec<u32>>();
+    let mut sent = PowerSumQuackU32::new(threshold);
+    let mut received = PowerSumQuackU32::new(threshold);
+    for (i, &id) in log.iter().enumerate() {
+        sent.insert(id);
+        if i >= num_missing {
+            received.insert(id);
+        }
+    }
+    (log, sent, received)
+}
+
+/// The (threshold, number of missing packets) pairs that can be decoded.
+fn params() -> impl Iterator<Item = (usize, usize)> {
+    THRESHOLDS.into_iter().flat_map(|threshold| {
+        NUM_MISSING
+            .into_iter()
+            .filter(move |&num_missing| num_missing <= threshold)
+            .map(move |num_missing| (threshold, num_missing))
+    })
+}
+
+fn bench_insert(c: &mut Criterion) {
+    setup();
+    let mut group = c.benchmark_group("insert");
+    for threshold in THRESHOLDS {
+        let mut quack = PowerSumQuackU32::new(threshold);
+        let mut rng = StdRng::seed_from_u64(SEED);
+        group.bench_function(BenchmarkId::from_parameter(threshold), |b| {
+            b.iter(|| quack.insert(black_box(rng.gen())))
+        });
+    }
+    group.finish();
+}
+
+/// Inserts all packets in a trial one at a time and as a batch.
+fn bench_insert_batch(c: &mut Criterion) {
+    setup();
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let log = (0..NUM_PACKETS).map(|_| rng.gen()).collect::<Vec<u32>>();
+    let mut group = c.benchmark_group("insert_batch");
+    for threshold in THRESHOLDS {
+        let mut quack = PowerSumQuackU32::new(threshold);
+        group.bench_function(BenchmarkId::new("loop", threshold), |b| {
+            b.iter(|| black_box(&log).iter().for_each(|&x| quack.insert(x)))
+        });
+        group.bench_function(BenchmarkId::new("batch", threshold), |b| {
+            b.iter(|| quack.insert_batch(black_box(&log)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_sub_assign(c: &mut Criterion) {
+    setup();
+    let mut group = c.benchmark_group("sub_assign");
+    for threshold in THRESHOLDS {
+        let (_, sent, received) = gen_quacks(threshold, 1);
+        group.bench_function(BenchmarkId::from_parameter(threshold), |b| {
+            b.iter_batched(
+                || (sent.clone(), received.clone()),
+                |(mut sent, received)| sent.sub_assign(black_box(received)),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_to_coeffs(c: &mut Criterion) {
+    setup();
+    let mut group = c.benchmark_group("to_coeffs");
+    for (threshold, num_missing) in params() {
+        let (_, sent, received) = gen_quacks(threshold, num_missing);
+        let diff = sent.sub(received);
+        group.bench_function(
+            BenchmarkId::new(format!("t={}", threshold), num_missing),
+            |b| b.iter(|| black_box(&diff).to_coeffs()),
+        );
+    }
+    group.finish();
+}
+
+fn bench_decode_with_log(c: &mut Criterion) {
+    setup();
+    let mut group = c.benchmark_group("decode_with_log");
+    for (threshold, num_missing) in params() {
+        let (log, sent, received) = gen_quacks(threshold, num_missing);
+        let diff = sent.sub(received);
+        group.bench_function(
+            BenchmarkId::new(format!("t={}", threshold), num_missing),
+            |b| b.iter(|| black_box(&diff).decode_with_log(black_box(&log))),
+        );
+    }
+    group.finish();
+}
+
+/// Decodes a log of 100k packets, which the `rayon` feature splits across
+/// threads. Compare runs with and without `--features rayon`.
+fn bench_decode_long_log(c: &mut Criterion) {
+    setup();
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let log = (0..100_000).map(|_| rng.gen()).collect::<Vec<u32>>();
+    let mut group = c.benchmark_group("decode_long_log");
+    for threshold in [10, 100] {
+        let mut diff = PowerSumQuackU32::new(threshold);
+        log.iter()
+            .step_by(log.len() / 8)
+            .for_each(|&x| diff.insert(x));
+        group.bench_function(BenchmarkId::from_parameter(threshold), |b| {
+            b.iter(|| black_box(&diff).decode_with_log(black_box(&log)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_serialize(c: &mut Criterion) {
+    setup();
+    let mut group = c.benchmark_group("serialize");
+    for threshold in THRESHOLDS {
+        let (_, _, received) = gen_quacks(threshold, 0);
+        group.bench_function(BenchmarkId::from_parameter(threshold), |b| {
+            b.iter(|| bincode::serialize(black_box(&received)).unwrap())
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("deserialize");
+    for threshold in THRESHOLDS {
+        let (_, _, received) = gen_quacks(threshold, 0);
+        let bytes = bincode::serialize(&received).unwrap();
+        group.bench_function(BenchmarkId::from_parameter(threshold), |b| {
+            b.iter(|| {
+                let quack: PowerSumQuackU32 = bincode::deserialize(black_box(&bytes)).unwrap();
+                quack
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_insert,
+    bench_insert_batch,
+    bench_sub_assign,
+    bench_to_coeffs,
+    bench_decode_with_log,
+    bench_decode_long_log,
+    bench_serialize
+);
+criterion_main!(benches);
@@ -10,7 +10,7 @@ use rand::{
 use serde::Serialize;
 use sha2::{Digest, Sha256};
 
-use quack::*;
+use quack::prelude::*;
 
 #[derive(Clone, ValueEnum, Debug, PartialEq, Eq)]
 pub enum QuackType {
@@ -99,11 +99,9 @@ pub fn print_summary(d: Vec<Duration>, cycles: Vec<u64>, num_packets: usize) {
     let d_per_packet = avg_time / num_packets as u32;
     let cycles_per_packet = avg_cycles / num_packets as u64;
     let ns_per_packet = d_per_packet.as_secs() * 1000000000 + d_per_packet.subsec_nanos() as u64;
-    let packets_per_s = if ns_per_packet == 0 {
-        "NaN".to_string()
-    } else {
-        (1000000000 / ns_per_packet).to_string()
-    };
+    let packets_per_s = 1000000000u64
+        .checked_div(ns_per_packet)
+        .map_or("NaN".to_string(), |x| x.to_string());
     warn!(
         "SUMMARY (per-packet): {:?}/packet = {} packets/s = {} cycles/packet",
         d_per_packet, packets_per_s, cycles_per_packet,
@@ -1,6 +1,6 @@
 use clap::Parser;
 use log::{info, warn};
-use quack::*;
+use quack::prelude::*;
 use rand::{
     distributions::{Distribution, Standard},
     Rng,
@@ -10,7 +10,7 @@ use rand::{
 };
 use sha2::{Digest, Sha256};
 
-use quack::*;
+use quack::prelude::*;
 
 #[derive(Parser, Debug)]
 pub struct QuackParams {
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+use log::{debug, info, warn};
+use rand::Rng;
+use tokio::net::UdpSocket;
+use tokio::time;
+
+use quack::prelude::*;
+use quack::receiver::{EmitPolicy, Receiver};
+use quack::sync::SharedQuack;
+
+#[derive(Parser, Debug)]
+struct Cli {
+    /// The threshold number of dropped packets.
+    #[arg(long, short = 't', default_value_t = 20)]
+    threshold: usize,
+    /// Number of packets to send per connection.
+    #[arg(short = 'n', default_value_t = 1000)]
+    num_packets: usize,
+    /// Number of concurrent connections through the proxy.
+    #[arg(long, short = 'c', default_value_t = 2)]
+    connections: usize,
+    /// Drop every k-th packet before it is sent.
+    #[arg(long = "drop-every", default_value_t = 100)]
+    drop_every: usize,
+    /// Microseconds to wait between sent packets.
+    #[arg(long = "send-interval-us", default_value_t = 100)]
+    send_interval_us: u64,
+    /// Maximum milliseconds between quACKs sent by the proxy.
+    #[arg(long = "quack-interval-ms", default_value_t = 10)]
+    quack_interval_ms: u64,
+    /// Maximum received packets between quACKs sent by the proxy.
+    #[arg(long = "quack-every", default_value_t = 100)]
+    quack_every: u32,
+}
+
+/// Inserts each packet identifier received by the proxy in the quACK of the
+/// connection it was received from, and sends each connection its quACK
+/// whenever the emit policy triggers on a packet or on a timer tick.
+async fn run_proxy(socket: UdpSocket, threshold: usize, policy: EmitPolicy) -> io::Result<()> {
+    let mut receivers: HashMap<SocketAddr, Receiver<PowerSumQuackU32>> = HashMap::new();
+    let mut ticker = time::interval(Duration::from_millis(1));
+    let mut buf = [0u8; 1500];
+    loop {
+        tokio::select! {
+            result = socket.recv_from(&mut buf) => {
+                let (len, src) = result?;
+                if len != 4 {
+                    warn!("ignoring {} byte datagram from {}", len, src);
+                    continue;
+                }
+                let id = u32::from_be_bytes(buf[..4].try_into().unwrap());
+                let receiver = receivers
+                    .entry(src)
+                    .or_insert_with(|| Receiver::new(threshold, policy.clone()));
+                if let Some(bytes) = receiver.insert(id, Instant::now()) {
+                    socket.send_to(&bytes, src).await?;
+                }
+            }
+            _ = ticker.tick() => {
+                let now = Instant::now();
+                for (&addr, receiver) in receivers.iter_mut() {
+                    if let Some(bytes) = receiver.poll(now) {
+                        socket.send_to(&bytes, addr).await?;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Decodes the quACKs received from the proxy against the quACK of sent
+/// packets. Once the sender is done, returns the missing packets when two
+/// consecutive quACKs agree on them, or after the proxy goes quiet.
+async fn decode_quacks(
+    socket: Arc<UdpSocket>,
+    sent: SharedQuack,
+    ids: Arc<Vec<u32>>,
+    done: Arc<AtomicBool>,
+    timeout: Duration,
+) -> io::Result<Vec<u32>> {
+    let mut buf = [0u8; 1500];
+    let mut missing: Option<Vec<u32>> = None;
+    loop {
+        let len = match time::timeout(timeout, socket.recv(&mut buf)).await {
+            Ok(result) => result?,
+            Err(_) if done.load(Ordering::SeqCst) => {
+                return missing
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "no quACK decoded"));
+            }
+            Err(_) => continue,
+        };
+        match sent.sub_and_decode_from_bytes(&buf[..len], &ids) {
+            Ok(mut decoded) => {
+                decoded.sort();
+                debug!("{} packets missing at the proxy", decoded.len());
+                if done.load(Ordering::SeqCst) && missing.as_ref() == Some(&decoded) {
+                    return Ok(decoded);
+                }
+                missing = Some(decoded);
+            }
+            // Usually too many packets are still in flight.
+            Err(e @ Error::ExceedsThreshold { .. }) => {
+                debug!("cannot decode quACK: {}", e)
+            }
+            Err(e) => warn!("cannot decode quACK: {}", e),
+        }
+    }
+}
+
+/// Sends the identifiers to the proxy, skipping every `drop_every`-th packet
+/// to simulate loss, and returns the identifiers detected as lost.
+async fn run_connection(
+    proxy: SocketAddr,
+    threshold: usize,
+    ids: Vec<u32>,
+    drop_every: usize,
+    send_interval: Duration,
+    quack_timeout: Duration,
+) -> io::Result<Vec<u32>> {
+    let socket = Arc::new(UdpSocket::bind("127.0.0.1:0").await?);
+    let sent = SharedQuack::new(threshold);
+    let ids = Arc::new(ids);
+    let done = Arc::new(AtomicBool::new(false));
+    let decoder = tokio::spawn(decode_quacks(
+        socket.clone(),
+        sent.clone(),
+        ids.clone(),
+        done.clone(),
+        quack_timeout,
+    ));
+
+    for (i, &id) in ids.iter().enumerate() {
+        sent.insert(id);
+        if drop_every > 0 && (i + 1) % drop_every == 0 {
+            debug!("dropping packet {} (id = {})", i, id);
+            continue;
+        }
+        socket.send_to(&id.to_be_bytes(), proxy).await?;
+        time::sleep(send_interval).await;
+    }
+    done.store(true, Ordering::SeqCst);
+    decoder.await?
+}
+
+/// Starts the proxy on a loopback address and returns its address.
+async fn start_proxy(threshold: usize, policy: EmitPolicy) -> io::Result<SocketAddr> {
+    let socket = UdpSocket::bind("127.0.0.1:0").await?;
+    let addr = socket.local_addr()?;
+    tokio::spawn(run_proxy(socket, threshold, policy));
+    Ok(addr)
+}
+
+#[tokio::main]
+async fn main() -> io::Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let args = Cli::parse();
+    quack::global_config_set_max_power_sum_threshold(args.threshold);
+
+    let quack_interval = Duration::from_millis(args.quack_interval_ms);
+    let policy = EmitPolicy::hybrid(args.quack_every, quack_interval);
+    let proxy = start_proxy(args.threshold, policy).await?;
+    info!("proxy bound to {}", proxy);
+
+    let handles = (0..args.connections)
+        .map(|_| {
+            let ids = (0..args.num_packets)
+                .map(|_| rand::thread_rng().gen())
+                .collect::<Vec<u32>>();
+            tokio::spawn(run_connection(
+                proxy,
+                args.threshold,
+                ids,
+                args.drop_every,
+                Duration::from_micros(args.send_interval_us),
+                10 * quack_interval,
+            ))
+        })
+        .collect::<Vec<_>>();
+    for (i, handle) in handles.into_iter().enumerate() {
+        let lost = handle.await??;
+        for id in &lost {
+            info!("connection {}: lost packet id = {}", i, id);
+        }
+        warn!(
+            "SUMMARY: connection {} sent {} packets, detected {} lost",
+            i,
+            args.num_packets,
+            lost.len()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_detects_injected_drops_per_connection() {
+        const THRESHOLD: usize = 20;
+        const NUM_PACKETS: u32 = 300;
+        const DROP_EVERY: usize = 30;
+        quack::global_config_set_max_power_sum_threshold(THRESHOLD);
+
+        let quack_interval = Duration::from_millis(5);
+        let policy = EmitPolicy::hybrid(50, quack_interval);
+        let proxy = start_proxy(THRESHOLD, policy).await.unwrap();
+        let handles = (1..=2u32)
+            .map(|conn| {
+                let ids = (1..=NUM_PACKETS)
+                    .map(|i| i * 7919 + conn)
+                    .collect::<Vec<_>>();
+                let handle = tokio::spawn(run_connection(
+                    proxy,
+                    THRESHOLD,
+                    ids.clone(),
+                    DROP_EVERY,
+                    Duration::from_micros(50),
+                    Duration::from_secs(1),
+                ));
+                (ids, handle)
+            })
+            .collect::<Vec<_>>();
+
+        for (ids, handle) in handles {
+            let lost = handle.await.unwrap().unwrap();
+            let expected = ids
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| (i + 1) % DROP_EVERY == 0)
+                .map(|(_, &id)| id)
+                .collect::<Vec<_>>();
+            assert_eq!(lost, expected);
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use std::collections::HashSet;
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+use std::thread;
+use std::time::Duration;
+
+use clap::{Parser, ValueEnum};
+use log::{debug, info, warn};
+use rand::Rng;
+
+use quack::frame::QuackFrame;
+use quack::prelude::*;
+
+/// Sent by the receiver every `--every` packets.
+const KIND_PERIODIC: u8 = 0;
+/// Sent by the receiver in response to an empty datagram from the sender.
+const KIND_FINAL: u8 = 1;
+/// Number of times the sender re-requests the final quACK on a read timeout.
+const MAX_FLUSH_RETRIES: usize = 3;
+
+#[derive(Clone, ValueEnum, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Sender,
+    Receiver,
+}
+
+#[derive(Parser, Debug)]
+struct Cli {
+    /// Whether to run the sender or the receiver end.
+    #[arg(value_enum)]
+    mode: Mode,
+    /// Address to bind the UDP socket to.
+    #[arg(long, default_value = "127.0.0.1:0")]
+    bind: SocketAddr,
+    /// Address of the receiver (sender mode only).
+    #[arg(long, default_value = "127.0.0.1:5103")]
+    peer: SocketAddr,
+    /// The threshold number of dropped packets.
+    #[arg(long, short = 't', default_value_t = 20)]
+    threshold: usize,
+    /// Number of packets to send (sender mode only).
+    #[arg(short = 'n', default_value_t = 1000)]
+    num_packets: usize,
+    /// Drop every k-th packet before it is sent (sender mode only).
+    #[arg(long = "drop-every", default_value_t = 100)]
+    drop_every: usize,
+    /// Microseconds to wait between sent packets (sender mode only).
+    #[arg(long = "interval-us", default_value_t = 100)]
+    interval_us: u64,
+    /// Number of received packets between quACKs (receiver mode only).
+    #[arg(long, default_value_t = 100)]
+    every: usize,
+}
+
+/// Receives packet identifiers until the sender asks for a final quACK,
+/// replying with the cumulative quACK every `every` packets. Each reply is a
+/// one-byte kind followed by a quACK frame with an increasing sequence number.
+fn run_receiver(socket: &UdpSocket, threshold: usize, every: usize) -> io::Result<u32> {
+    let mut quack = QuackBuilder::power_sum()
+        .threshold(threshold)
+        .build()
+        .map_err(io::Error::other)?;
+    let mut seq = 0;
+    let mut buf = [0u8; 1500];
+    loop {
+        let (len, src) = socket.recv_from(&mut buf)?;
+        if len == 0 {
+            reply(socket, src, KIND_FINAL, seq, &quack)?;
+            return Ok(quack.count());
+        }
+        if len != 4 {
+            warn!("ignoring {} byte datagram from {}", len, src);
+            continue;
+        }
+        quack.insert(u32::from_be_bytes(buf[..4].try_into().unwrap()));
+        if (quack.count() as usize).is_multiple_of(every) {
+            reply(socket, src, KIND_PERIODIC, seq, &quack)?;
+            seq += 1;
+        }
+    }
+}
+
+fn reply(
+    socket: &UdpSocket,
+    dst: SocketAddr,
+    kind: u8,
+    seq: u32,
+    quack: &PowerSumQuackU32,
+) -> io::Result<()> {
+    let frame = QuackFrame {
+        seq,
+        path_id: None,
+        quack: quack.clone(),
+    };
+    let mut buf = [0u8; 1500];
+    buf[0] = kind;
+    let len = frame.encode_into(&mut buf[1..]).map_err(io::Error::other)?;
+    socket.send_to(&buf[..(1 + len)], dst)?;
+    Ok(())
+}
+
+/// Sends the identifiers to the receiver, skipping every `drop_every`-th
+/// packet to simulate loss, and returns the identifiers the sender detects
+/// as lost from the receiver's quACKs. Packets are paced by `interval` so the
+/// receiver's socket buffer does not overflow on loopback.
+fn run_sender(
+    socket: &UdpSocket,
+    peer: SocketAddr,
+    threshold: usize,
+    ids: &[u32],
+    drop_every: usize,
+    interval: Duration,
+) -> io::Result<Vec<u32>> {
+    let mut sent = QuackBuilder::power_sum()
+        .threshold(threshold)
+        .build()
+        .map_err(io::Error::other)?;
+    for (i, &id) in ids.iter().enumerate() {
+        sent.insert(id);
+        if drop_every > 0 && (i + 1) % drop_every == 0 {
+            debug!("dropping packet {} (id = {})", i, id);
+            continue;
+        }
+        socket.send_to(&id.to_be_bytes(), peer)?;
+        thread::sleep(interval);
+    }
+    socket.send_to(&[], peer)?;
+
+    let mut lost = vec![];
+    let mut reported = HashSet::new();
+    let mut next_seq = 0;
+    let mut buf = [0u8; 1500];
+    let mut retries = 0;
+    loop {
+        let len = match socket.recv(&mut buf) {
+            Ok(len) => len,
+            Err(e) if is_timeout(&e) && retries < MAX_FLUSH_RETRIES => {
+                // The request for the final quACK may have been lost too.
+                retries += 1;
+                socket.send_to(&[], peer)?;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        if len == 0 {
+            continue;
+        }
+        let frame = match QuackFrame::decode(&buf[1..len]) {
+            Ok((frame, _)) => frame,
+            Err(e) => {
+                warn!("ignoring malformed quACK: {}", e);
+                continue;
+            }
+        };
+        let is_final = buf[0] == KIND_FINAL;
+        if frame.seq < next_seq && !is_final {
+            debug!("ignoring stale quACK {}", frame.seq);
+            continue;
+        }
+        next_seq = frame.seq + 1;
+        let received = frame.quack;
+
+        // Only packets sent up to the last packet the receiver saw can be
+        // considered lost, unless this is the final quACK.
+        let log = if is_final {
+            ids
+        } else {
+            match received.last_value() {
+                Some(last) => match ids.iter().position(|&id| id == last) {
+                    Some(index) => &ids[..=index],
+                    None => continue,
+                },
+                None => continue,
+            }
+        };
+
+        let mut diff = sent.clone();
+        for &id in &ids[log.len()..] {
+            diff.remove(id);
+        }
+        diff.sub_assign(received);
+        if diff.count() as usize > threshold {
+            warn!(
+                "{} missing packets exceed the threshold {}",
+                diff.count(),
+                threshold
+            );
+        } else {
+            for id in diff.decode_with_log(log) {
+                if reported.insert(id) {
+                    info!("lost packet id = {}", id);
+                    lost.push(id);
+                }
+            }
+        }
+        if is_final {
+            return Ok(lost);
+        }
+    }
+}
+
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
+fn main() -> io::Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let args = Cli::parse();
+
+    let socket = UdpSocket::bind(args.bind)?;
+    info!("bound to {}", socket.local_addr()?);
+    match args.mode {
+        Mode::Receiver => {
+            let count = run_receiver(&socket, args.threshold, args.every)?;
+            info!("received {} packets", count);
+        }
+        Mode::Sender => {
+            socket.set_read_timeout(Some(Duration::from_secs(1)))?;
+            let ids = (0..args.num_packets)
+                .map(|_| rand::thread_rng().gen())
+                .collect::<Vec<u32>>();
+            let interval = Duration::from_micros(args.interval_us);
+            let lost = run_sender(
+                &socket,
+                args.peer,
+                args.threshold,
+                &ids,
+                args.drop_every,
+                interval,
+            )?;
+            warn!(
+                "SUMMARY: sent {} packets, detected {} lost",
+                ids.len(),
+                lost.len()
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_detects_injected_drops() {
+        const THRESHOLD: usize = 20;
+        const NUM_PACKETS: u32 = 200;
+        const DROP_EVERY: usize = 20;
+
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let peer = receiver.local_addr().unwrap();
+        let handle = thread::spawn(move || run_receiver(&receiver, THRESHOLD, 25).unwrap());
+
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        sender
+            .set_read_timeout(Some(Duration::from_secs(1)))
+            .unwrap();
+        let ids = (1..=NUM_PACKETS).map(|i| i * 7919).collect::<Vec<_>>();
+        let interval = Duration::from_micros(50);
+        let mut lost = run_sender(&sender, peer, THRESHOLD, &ids, DROP_EVERY, interval).unwrap();
+        lost.sort();
+
+        let expected = ids
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| (i + 1) % DROP_EVERY == 0)
+            .map(|(_, &id)| id)
+            .collect::<Vec<_>>();
+        assert_eq!(lost, expected);
+        assert_eq!(handle.join().unwrap(), NUM_PACKETS - expected.len() as u32);
+    }
+}
@@ -0,0 +1,18 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use quack::frame::QuackFrame;
+use quack::sender::SentLog;
+use quack::PowerSumQuack;
+
+fuzz_target!(|data: &[u8]| {
+    let mut rest = data;
+    while let Ok((frame, len)) = QuackFrame::decode(rest) {
+        let mut log = SentLog::new(frame.quack.threshold());
+        for x in 1..=40 {
+            log.record(x, ()).unwrap();
+        }
+        log.on_quack(&frame.quack);
+        rest = &rest[len..];
+    }
+});
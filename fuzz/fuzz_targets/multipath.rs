@@ -0,0 +1,22 @@
+#![no_main]
+
+use std::collections::HashMap;
+
+use libfuzzer_sys::fuzz_target;
+use quack::multipath::QuackSet;
+
+const THRESHOLD: usize = 20;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(set) = QuackSet::deserialize(data, THRESHOLD) {
+        let mut sent = QuackSet::new(THRESHOLD);
+        let mut logs = HashMap::new();
+        for path_id in set.paths().collect::<Vec<_>>() {
+            let log = (1..=10).collect::<Vec<u32>>();
+            log.iter().for_each(|&x| sent.insert(path_id, x));
+            logs.insert(path_id, log);
+        }
+        sent.sub_assign(&set);
+        let _ = sent.decode_with_logs(&logs);
+    }
+});
@@ -0,0 +1,20 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use quack::negotiate::{resolve, IdStrategy, Message, Negotiable};
+use quack::PowerSumQuackU32;
+
+fuzz_target!(|data: &[u8]| {
+    let supported = [PowerSumQuackU32::params(20, IdStrategy::Ipv4)];
+    match Message::deserialize(data) {
+        Ok(Message::Offer { kinds }) => {
+            if let Some(chosen) = resolve(&kinds, &supported) {
+                let _ = chosen.try_build::<PowerSumQuackU32>();
+            }
+        }
+        Ok(Message::Accept { chosen }) => {
+            let _ = chosen.try_build::<PowerSumQuackU32>();
+        }
+        Err(_) => {}
+    }
+});
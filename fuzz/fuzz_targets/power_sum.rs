@@ -0,0 +1,34 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use quack::sync::SharedQuack;
+use quack::{PowerSumQuack, PowerSumQuackU32};
+
+const THRESHOLD: usize = 20;
+
+fuzz_target!(|data: &[u8]| {
+    let log = (1..=40).collect::<Vec<u32>>();
+    if let Ok((quack, _)) = PowerSumQuackU32::deserialize_bounded(data, THRESHOLD) {
+        let _ = quack.try_decode_with_log(&log);
+        let _ = quack.decode_with_log_detailed(&log);
+    }
+
+    if let Ok((quack, _)) = PowerSumQuackU32::deserialize_with_checksum(data, THRESHOLD) {
+        let _ = quack.try_decode_with_log(&log);
+    }
+    if let Ok((quack, _)) = PowerSumQuackU32::deserialize_compact(data, THRESHOLD) {
+        let _ = quack.try_decode_with_log(&log);
+    }
+
+    let mut quack = PowerSumQuackU32::new(THRESHOLD);
+    if quack.apply_delta(data).is_ok() {
+        let _ = quack.try_to_coeffs();
+    }
+    if quack.deserialize_into(data).is_ok() {
+        let _ = quack.try_to_coeffs();
+    }
+
+    let shared = SharedQuack::new(THRESHOLD);
+    (1..=10).for_each(|x| shared.insert(x));
+    let _ = shared.sub_and_decode_from_bytes(data, &log);
+});
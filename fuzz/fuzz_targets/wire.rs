@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use quack::wire;
+use quack::{PowerSumQuack, PowerSumQuackU32};
+
+fuzz_target!(|data: &[u8]| {
+    let log = (1..=20).map(|x| x * 1000).collect::<Vec<_>>();
+    if let Ok(quack) = wire::decode::<PowerSumQuackU32>(data) {
+        let _ = quack.try_decode_with_log(&log);
+        let _ = quack.decode_with_log_detailed(&log);
+    }
+    if let Ok(quack) = wire::decode_with_checksum::<PowerSumQuackU32>(data) {
+        let _ = quack.try_decode_with_log(&log);
+    }
+});
@@ -57,7 +57,7 @@ cfg_montgomery! {
     ///
     /// The polynomial evaluated at `x`, in Montgomery form.
     pub fn eval_montgomery(
-        coeffs: &Vec<MontgomeryInteger>,
+        coeffs: &CoefficientVector<MontgomeryInteger>,
         x: u64,
     ) -> MontgomeryInteger {
         let size = coeffs.len();
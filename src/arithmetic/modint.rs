@@ -1,12 +1,13 @@
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
 use std::cmp::PartialEq;
+use std::fmt::Display;
 
 /// An element in the finite field with integers modulo a prime.
 ///
 /// Uses `T`, an unsigned integer type, as the underlying representation. The
 /// field is integers modulo the largest prime that fits in `T`. The crate
 /// contains implementations for `u16`, `u32`, and `u64`.
-#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize)]
 pub struct ModularInteger<T> {
     value: T,
 }
@@ -92,6 +93,42 @@ impl<T: PartialEq> PartialEq<T> for ModularInteger<T> {
     }
 }
 
+/// The serialized form of a modular integer, which has the same layout as the
+/// modular integer but may not be in the field.
+#[derive(Deserialize)]
+struct SerializedValue<T> {
+    value: T,
+}
+
+/// Deserializes a modular integer, returning an error instead of reducing the
+/// value if it is not less than the modulus. Arithmetic on an element outside
+/// the field may overflow, and the value is untrusted.
+pub(crate) fn deserialize_in_field<'de, D, M>(deserializer: D) -> Result<M, D::Error>
+where
+    D: Deserializer<'de>,
+    M: ModularArithmetic,
+    M::SmallModulusType: Deserialize<'de> + PartialOrd + Display,
+{
+    let SerializedValue { value } = SerializedValue::deserialize(deserializer)?;
+    if value >= M::modulus() {
+        return Err(de::Error::custom(format!(
+            "{value} is not less than the modulus {}",
+            M::modulus()
+        )));
+    }
+    Ok(M::new(value))
+}
+
+impl<'de, T> Deserialize<'de> for ModularInteger<T>
+where
+    ModularInteger<T>: ModularArithmetic<SmallModulusType = T>,
+    T: Deserialize<'de> + PartialOrd + Display,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_in_field(deserializer)
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
 impl ModularArithmetic for ModularInteger<u32> {
@@ -358,9 +395,24 @@ mod test {
     use super::*;
     use once_cell::sync::Lazy;
 
-    static U16_MODULUS: Lazy<u16> = Lazy::new(|| ModularInteger::<u16>::modulus());
-    static U32_MODULUS: Lazy<u32> = Lazy::new(|| ModularInteger::<u32>::modulus());
-    static U64_MODULUS: Lazy<u64> = Lazy::new(|| ModularInteger::<u64>::modulus());
+    static U16_MODULUS: Lazy<u16> = Lazy::new(ModularInteger::<u16>::modulus);
+    static U32_MODULUS: Lazy<u32> = Lazy::new(ModularInteger::<u32>::modulus);
+    static U64_MODULUS: Lazy<u64> = Lazy::new(ModularInteger::<u64>::modulus);
+
+    #[test]
+    fn test_deserialize_outside_field() {
+        let bytes = bincode::serialize(&(*U32_MODULUS - 1)).unwrap();
+        let x: ModularInteger<u32> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(x, *U32_MODULUS - 1);
+        for value in [*U32_MODULUS, u32::MAX] {
+            let bytes = bincode::serialize(&value).unwrap();
+            assert!(bincode::deserialize::<ModularInteger<u32>>(&bytes).is_err());
+        }
+        let bytes = bincode::serialize(&*U16_MODULUS).unwrap();
+        assert!(bincode::deserialize::<ModularInteger<u16>>(&bytes).is_err());
+        let bytes = bincode::serialize(&*U64_MODULUS).unwrap();
+        assert!(bincode::deserialize::<ModularInteger<u64>>(&bytes).is_err());
+    }
 
     #[test]
     fn test_zero_constructor() {
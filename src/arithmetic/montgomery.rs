@@ -1,6 +1,7 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::cmp::PartialEq;
 use crate::arithmetic::ModularArithmetic;
+use super::modint::deserialize_in_field;
 
 // N
 const N: u64 = 18_446_744_073_709_551_557;
@@ -23,11 +24,17 @@ const N_NEGMODINV_R: u128 = 14_694_863_923_124_558_067;
 /// and `R > N` to be useful for modular multiplication. The implementation
 /// uses `N = 18446744073709551557`, the largest 64-bit prime, and
 /// `R = 1 << 64`, a co-prime power of two with efficient division and modulus.
-#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize)]
 pub struct MontgomeryInteger {
     value: u64,
 }
 
+impl<'de> Deserialize<'de> for MontgomeryInteger {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_in_field(deserializer)
+    }
+}
+
 impl MontgomeryInteger where MontgomeryInteger: ModularArithmetic {
     /// Create a new Montgomery integer, doing the conversion from the original
     /// integer to the integer in Montgomery form.
@@ -183,6 +190,15 @@ mod test {
         (product % (N as u128)) as u64
     }
 
+    #[test]
+    fn test_deserialize_outside_field() {
+        let bytes = bincode::serialize(&(N - 1)).unwrap();
+        let x: MontgomeryInteger = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(x.value(), N - 1);
+        let bytes = bincode::serialize(&N).unwrap();
+        assert!(bincode::deserialize::<MontgomeryInteger>(&bytes).is_err());
+    }
+
     #[test]
     fn test_zero_constructor() {
         assert_eq!(MontgomeryInteger::new(0), 0);
@@ -0,0 +1,237 @@
+//! Construction of quACKs with a checked threshold.
+use crate::precompute;
+use crate::{Error, PowerSumQuack, PowerSumQuackU32};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// The threshold of a quACK built without [QuackBuilder::threshold], which is
+/// also the default maximum threshold of the precomputed tables.
+pub const DEFAULT_THRESHOLD: usize = 20;
+
+/// Builds an empty quACK of type `Q`.
+///
+/// Unlike `Q::new`, [QuackBuilder::build] makes sure the precomputed tables
+/// support the threshold, raising the maximum threshold set by
+/// [crate::global_config_set_max_power_sum_threshold] if needed, so there is
+/// no need to configure it before constructing the quACK.
+///
+/// # Examples
+///
+/// ```
+/// use quack::prelude::*;
+///
+/// let mut quack = QuackBuilder::power_sum().threshold(64).build().unwrap();
+/// quack.insert(1);
+/// assert_eq!(quack.threshold(), 64);
+///
+/// let quack = QuackBuilder::<PowerSumQuackU32>::new().threshold(0).build();
+/// assert_eq!(quack.unwrap_err(), Error::ZeroThreshold);
+/// ```
+pub struct QuackBuilder<Q> {
+    threshold: usize,
+    quack: PhantomData<fn() -> Q>,
+}
+
+impl<Q> Clone for QuackBuilder<Q> {
+    fn clone(&self) -> Self {
+        Self {
+            threshold: self.threshold,
+            quack: PhantomData,
+        }
+    }
+}
+
+impl<Q> fmt::Debug for QuackBuilder<Q> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QuackBuilder")
+            .field("threshold", &self.threshold)
+            .finish()
+    }
+}
+
+impl<Q: PowerSumQuack> Default for QuackBuilder<Q> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Q: PowerSumQuack> QuackBuilder<Q> {
+    /// Creates a builder for quACKs with the [DEFAULT_THRESHOLD].
+    pub fn new() -> Self {
+        Self {
+            threshold: DEFAULT_THRESHOLD,
+            quack: PhantomData,
+        }
+    }
+
+    /// Sets the maximum number of elements the quACK can decode.
+    pub fn threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Builds an empty quACK. Returns [Error::ZeroThreshold] if the threshold
+    /// is zero, or [Error::ThresholdTooLarge] if the precomputed tables were
+    /// already computed with a smaller maximum threshold.
+    pub fn build(&self) -> Result<Q, Error> {
+        if self.threshold == 0 {
+            return Err(Error::ZeroThreshold);
+        }
+        precompute::ensure_max_threshold(self.threshold)?;
+        Ok(Q::new(self.threshold))
+    }
+
+    /// Builds a quACK with the elements. Returns an error like
+    /// [QuackBuilder::build].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quack::prelude::*;
+    ///
+    /// let log = vec![1, 2, 3, 4, 5];
+    /// let builder = QuackBuilder::power_sum().threshold(64);
+    /// let sent = builder.build_from(log.iter().copied()).unwrap();
+    /// assert_eq!(sent.threshold(), 64);
+    /// assert_eq!(sent.count(), 5);
+    /// ```
+    pub fn build_from<I>(&self, elements: I) -> Result<Q, Error>
+    where
+        Q: Extend<Q::Element>,
+        I: IntoIterator<Item = Q::Element>,
+    {
+        let mut quack = self.build()?;
+        quack.extend(elements);
+        Ok(quack)
+    }
+}
+
+impl QuackBuilder<PowerSumQuackU32> {
+    /// Creates a builder for [PowerSumQuackU32].
+    pub fn power_sum() -> Self {
+        Self::new()
+    }
+}
+
+cfg_montgomery! {
+    impl QuackBuilder<crate::MontgomeryQuack> {
+        /// Creates a builder for [crate::MontgomeryQuack].
+        pub fn montgomery() -> Self {
+            Self::new()
+        }
+    }
+}
+
+cfg_power_table! {
+    impl QuackBuilder<crate::PowerTableQuack> {
+        /// Creates a builder for [crate::PowerTableQuack].
+        pub fn power_table() -> Self {
+            Self::new()
+        }
+    }
+}
+
+/// Builds a [PowerSumQuackU32] with the given elements, for tests and
+/// examples. The threshold defaults to [crate::builder::DEFAULT_THRESHOLD].
+///
+/// # Panics
+///
+/// Panics if [QuackBuilder::build] fails for the threshold.
+///
+/// # Examples
+///
+/// ```
+/// use quack::prelude::*;
+///
+/// let quack = quack![1, 2, 3; threshold = 10];
+/// assert_eq!(quack.threshold(), 10);
+/// assert_eq!(quack.count(), 3);
+///
+/// let mut diff = quack![1, 2, 3, 4, 5];
+/// diff.sub_assign(quack![1, 3, 5]);
+/// assert_eq!(diff.decode_with_log(&[1, 2, 3, 4, 5]), vec![2, 4]);
+/// ```
+#[macro_export]
+macro_rules! quack {
+    ($($elem:expr),* $(,)? ; threshold = $threshold:expr) => {{
+        #[allow(unused_mut)]
+        let mut quack = $crate::QuackBuilder::power_sum()
+            .threshold($threshold)
+            .build()
+            .unwrap_or_else(|e| panic!("{}", e));
+        $($crate::PowerSumQuack::insert(&mut quack, $elem);)*
+        quack
+    }};
+    ($($elem:expr),* $(,)?) => {
+        $crate::quack![$($elem),*; threshold = $crate::builder::DEFAULT_THRESHOLD]
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::precompute::INVERSE_TABLE_U32;
+
+    #[test]
+    fn test_build_power_sum() {
+        let quack = QuackBuilder::power_sum().build().unwrap();
+        assert_eq!(quack.threshold(), DEFAULT_THRESHOLD);
+        assert_eq!(quack.count(), 0);
+        let quack: PowerSumQuackU32 = QuackBuilder::new().threshold(5).build().unwrap();
+        assert_eq!(quack.threshold(), 5);
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let builder = QuackBuilder::power_sum().threshold(0);
+        assert_eq!(builder.build().unwrap_err(), Error::ZeroThreshold);
+
+        // once computed, the tables cannot be extended
+        let max = INVERSE_TABLE_U32.len();
+        let builder = QuackBuilder::power_sum().threshold(max + 1);
+        assert_eq!(
+            builder.build().unwrap_err(),
+            Error::ThresholdTooLarge {
+                threshold: max + 1,
+                max
+            }
+        );
+    }
+
+    #[test]
+    fn test_build_from() {
+        let builder = QuackBuilder::power_sum().threshold(5);
+        let quack = builder.build_from(1..=3).unwrap();
+        assert_eq!(
+            bincode::serialize(&quack).unwrap(),
+            bincode::serialize(&quack![1, 2, 3; threshold = 5]).unwrap()
+        );
+        let builder = QuackBuilder::power_sum().threshold(0);
+        assert_eq!(builder.build_from(1..=3).unwrap_err(), Error::ZeroThreshold);
+    }
+
+    #[test]
+    fn test_macro() {
+        let quack = quack![10, 20, 30,; threshold = 5];
+        assert_eq!(quack.threshold(), 5);
+        assert_eq!(quack.count(), 3);
+        assert_eq!(quack.last_value(), Some(30));
+
+        let empty = quack![; threshold = 5];
+        assert_eq!(empty.count(), 0);
+        assert_eq!(quack![].threshold(), DEFAULT_THRESHOLD);
+
+        let mut expected = PowerSumQuackU32::new(DEFAULT_THRESHOLD);
+        (1..=3).for_each(|x| expected.insert(x));
+        assert_eq!(
+            bincode::serialize(&quack![1, 2, 3]).unwrap(),
+            bincode::serialize(&expected).unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "threshold must be positive")]
+    fn test_macro_zero_threshold() {
+        quack![1; threshold = 0];
+    }
+}
@@ -0,0 +1,95 @@
+//! The CRC-32 (IEEE 802.3) checksum appended to serialized quACKs, the same
+//! checksum as in Ethernet, gzip, and PNG.
+use crate::Error;
+
+/// The number of bytes in a checksum.
+pub(crate) const LEN: usize = 4;
+
+/// The reversed CRC-32 polynomial.
+const POLYNOMIAL: u32 = 0xedb8_8320;
+
+/// The checksum of each byte, to process a byte at a time.
+const TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ POLYNOMIAL
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// The CRC-32 of the bytes.
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &byte| {
+        TABLE[usize::from((crc as u8) ^ byte)] ^ (crc >> 8)
+    })
+}
+
+/// Writes the checksum of the first `len` bytes of the buffer after them,
+/// returning the offset after the checksum. The buffer must have at least
+/// [LEN] bytes after the first `len` bytes.
+pub(crate) fn write(buf: &mut [u8], len: usize) -> usize {
+    let crc = crc32(&buf[..len]);
+    buf[len..len + LEN].copy_from_slice(&crc.to_le_bytes());
+    len + LEN
+}
+
+/// Checks the checksum after the first `len` bytes, returning the offset
+/// after the checksum.
+pub(crate) fn verify(bytes: &[u8], len: usize) -> Result<usize, Error> {
+    let needed = len + LEN;
+    let actual = bytes
+        .get(len..needed)
+        .ok_or(Error::BufferTooSmall { needed })?;
+    let actual = u32::from_le_bytes(actual.try_into().unwrap());
+    let expected = crc32(&bytes[..len]);
+    if actual != expected {
+        return Err(Error::ChecksumMismatch { expected, actual });
+    }
+    Ok(needed)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(
+            crc32(b"The quick brown fox jumps over the lazy dog"),
+            0x414f_a339
+        );
+    }
+
+    #[test]
+    fn test_write_and_verify() {
+        let mut buf = *b"quACK\0\0\0\0\0";
+        assert_eq!(write(&mut buf, 5), 9);
+        assert_eq!(verify(&buf, 5), Ok(9));
+        assert_eq!(
+            verify(&buf[..8], 5),
+            Err(Error::BufferTooSmall { needed: 9 })
+        );
+        for i in 0..9 {
+            let mut bad = buf;
+            bad[i] ^= 0x10;
+            assert!(matches!(
+                verify(&bad, 5),
+                Err(Error::ChecksumMismatch { .. })
+            ));
+        }
+    }
+}
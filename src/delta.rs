@@ -0,0 +1,177 @@
+//! Differences between successive quACKs from the same receiver.
+use crate::{PowerSumQuack, PowerSumQuackU32};
+use std::fmt;
+
+/// Why two quACKs from the same receiver could not be diffed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiffError {
+    /// The quACKs have different thresholds.
+    ThresholdMismatch {
+        /// The threshold of the older quACK.
+        older: usize,
+        /// The threshold of the newer quACK.
+        newer: usize,
+    },
+    /// The newer quACK represents fewer elements than the older quACK, so
+    /// they are out of order or not from the same receiver.
+    NonMonotonic {
+        /// The number of elements in the older quACK.
+        older: u32,
+        /// The number of elements in the newer quACK.
+        newer: u32,
+    },
+    /// More elements were received between the quACKs than can be decoded.
+    ThresholdExceeded {
+        /// The number of elements received between the quACKs.
+        count: u32,
+        /// The threshold of the quACKs.
+        threshold: usize,
+    },
+}
+
+impl fmt::Display for DiffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiffError::ThresholdMismatch { older, newer } => {
+                write!(f, "older quACK has threshold {older} but newer has {newer}")
+            }
+            DiffError::NonMonotonic { older, newer } => {
+                write!(f, "older quACK has {older} elements but newer has {newer}")
+            }
+            DiffError::ThresholdExceeded { count, threshold } => {
+                write!(f, "{count} new elements exceed the threshold {threshold}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DiffError {}
+
+/// The elements in the log that the receiver received after sending the
+/// `older` quACK and before sending the `newer` quACK.
+///
+/// Both quACKs must be cumulative quACKs from the same receiver. Subtracts
+/// the older quACK from the newer quACK and decodes the difference with the
+/// log, so the log should contain every element that may have been received
+/// in between. Elements in the log that were received before the older quACK
+/// or not at all are not returned.
+///
+/// # Examples
+///
+/// ```
+/// use quack::prelude::*;
+/// use quack::delta::newly_received;
+///
+/// let mut quack = PowerSumQuackU32::new(10);
+/// quack.insert(1);
+/// let older = quack.clone();
+/// quack.insert(3);
+/// assert_eq!(newly_received(&older, &quack, &[1, 2, 3]), Ok(vec![3]));
+/// ```
+pub fn newly_received(
+    older: &PowerSumQuackU32,
+    newer: &PowerSumQuackU32,
+    log: &[u32],
+) -> Result<Vec<u32>, DiffError> {
+    if older.threshold() != newer.threshold() {
+        return Err(DiffError::ThresholdMismatch {
+            older: older.threshold(),
+            newer: newer.threshold(),
+        });
+    }
+    if newer.count() < older.count() {
+        return Err(DiffError::NonMonotonic {
+            older: older.count(),
+            newer: newer.count(),
+        });
+    }
+    let count = newer.count() - older.count();
+    if count as usize > newer.threshold() {
+        return Err(DiffError::ThresholdExceeded {
+            count,
+            threshold: newer.threshold(),
+        });
+    }
+    if count == 0 {
+        return Ok(vec![]);
+    }
+    Ok(newer.clone().sub(older.clone()).decode_with_log(log))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    const THRESHOLD: usize = 10;
+
+    #[test]
+    fn test_successive_snapshots_partition_received() {
+        let log = (1..=30).collect::<Vec<u32>>();
+        let mut quack = PowerSumQuackU32::new(THRESHOLD);
+        let mut snapshots = vec![quack.clone()];
+        for chunk in log.chunks(10) {
+            // the receiver never receives multiples of 4
+            for &x in chunk.iter().filter(|&&x| x % 4 != 0) {
+                quack.insert(x);
+            }
+            snapshots.push(quack.clone());
+        }
+
+        let mut acked = HashSet::new();
+        for window in snapshots.windows(2) {
+            let new = newly_received(&window[0], &window[1], &log).unwrap();
+            assert_eq!(new.len() as u32, window[1].count() - window[0].count());
+            for x in new {
+                assert!(acked.insert(x), "{} acked twice", x);
+            }
+        }
+        let received = log.iter().copied().filter(|x| x % 4 != 0).collect();
+        assert_eq!(acked, received);
+    }
+
+    #[test]
+    fn test_no_new_elements() {
+        let mut quack = PowerSumQuackU32::new(THRESHOLD);
+        quack.insert(1);
+        assert_eq!(newly_received(&quack, &quack, &[1, 2]), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_non_monotonic() {
+        let older = PowerSumQuackU32::new(THRESHOLD);
+        let mut newer = older.clone();
+        newer.insert(1);
+        assert_eq!(
+            newly_received(&newer, &older, &[1]),
+            Err(DiffError::NonMonotonic { older: 1, newer: 0 })
+        );
+    }
+
+    #[test]
+    fn test_threshold_exceeded() {
+        let older = PowerSumQuackU32::new(THRESHOLD);
+        let mut newer = older.clone();
+        for x in 0..=(THRESHOLD as u32) {
+            newer.insert(x);
+        }
+        assert_eq!(
+            newly_received(&older, &newer, &[]),
+            Err(DiffError::ThresholdExceeded {
+                count: THRESHOLD as u32 + 1,
+                threshold: THRESHOLD,
+            })
+        );
+    }
+
+    #[test]
+    fn test_threshold_mismatch() {
+        let older = PowerSumQuackU32::new(THRESHOLD);
+        let newer = PowerSumQuackU32::new(THRESHOLD + 1);
+        let err = newly_received(&older, &newer, &[]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "older quACK has threshold 10 but newer has 11"
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! The error type for fallible quACK operations.
+use crate::delta::DiffError;
+use crate::frame::{DecodeError, EncodeError};
+use crate::negotiate::QuackKind;
+use std::fmt;
+
+/// A specialized `Result` type for quACK operations.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Why a quACK operation failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The quACKs have different thresholds.
+    ThresholdMismatch {
+        /// The threshold of the quACK being operated on.
+        expected: usize,
+        /// The threshold of the other quACK.
+        actual: usize,
+    },
+    /// More elements would be removed than the quACK has.
+    CountUnderflow {
+        /// The number of elements in the quACK.
+        count: u32,
+        /// The number of elements to remove.
+        removed: u32,
+    },
+    /// The buffer is too small. For decoding, the buffer only holds part of
+    /// the input, so try again with at least `needed` bytes.
+    BufferTooSmall {
+        /// The minimum number of bytes needed.
+        needed: usize,
+    },
+    /// The bytes are not a valid encoding.
+    MalformedBuffer(String),
+    /// The quACK type does not match the negotiated quACK kind and element
+    /// width, or the ones in the header of an encoded quACK.
+    TypeMismatch {
+        /// The negotiated or encoded quACK kind.
+        kind: QuackKind,
+        /// The negotiated or encoded number of bits in each element.
+        bits: u8,
+    },
+    /// The quACK has more elements than it can decode.
+    ExceedsThreshold {
+        /// The number of elements in the quACK.
+        count: u32,
+        /// The threshold of the quACK.
+        threshold: usize,
+    },
+    /// The wire version is not supported.
+    UnsupportedVersion(u8),
+    /// The threshold is zero, so the quACK cannot represent any elements.
+    ZeroThreshold,
+    /// The threshold is greater than the maximum threshold of the precomputed
+    /// tables, which were computed before the maximum could be raised.
+    ThresholdTooLarge {
+        /// The threshold of the quACK.
+        threshold: usize,
+        /// The maximum threshold of the precomputed tables.
+        max: usize,
+    },
+    /// The threshold of a quACK with elements cannot grow without the
+    /// elements, or the given elements are not the elements in the quACK.
+    CannotGrow {
+        /// The number of elements in the quACK.
+        count: u32,
+    },
+    /// The checksum of the bytes does not match the checksum after them, so
+    /// the bytes were corrupted.
+    ChecksumMismatch {
+        /// The checksum of the bytes.
+        expected: u32,
+        /// The checksum after the bytes.
+        actual: u32,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ThresholdMismatch { expected, actual } => {
+                write!(f, "expected threshold {expected}, got {actual}")
+            }
+            Error::CountUnderflow { count, removed } => {
+                write!(
+                    f,
+                    "cannot remove {removed} elements from a quACK with {count}"
+                )
+            }
+            Error::BufferTooSmall { needed } => write!(f, "buffer too small, need {needed} bytes"),
+            Error::MalformedBuffer(reason) => write!(f, "malformed buffer: {reason}"),
+            Error::TypeMismatch { kind, bits } => {
+                write!(f, "quACK type is not the expected {bits}-bit {kind:?}")
+            }
+            Error::ExceedsThreshold { count, threshold } => {
+                write!(f, "{count} elements exceed the threshold of {threshold}")
+            }
+            Error::UnsupportedVersion(version) => write!(f, "unsupported wire version {version}"),
+            Error::ZeroThreshold => write!(f, "threshold must be positive"),
+            Error::ThresholdTooLarge { threshold, max } => {
+                write!(
+                    f,
+                    "threshold {threshold} exceeds the precomputed maximum {max}"
+                )
+            }
+            Error::CannotGrow { count } => {
+                write!(
+                    f,
+                    "cannot grow the threshold of a quACK with {count} elements"
+                )
+            }
+            Error::ChecksumMismatch { expected, actual } => {
+                write!(f, "expected checksum {expected:#010x}, got {actual:#010x}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<bincode::Error> for Error {
+    fn from(e: bincode::Error) -> Self {
+        Error::MalformedBuffer(e.to_string())
+    }
+}
+
+/// A frame that is too large to encode maps to [Error::MalformedBuffer].
+impl From<EncodeError> for Error {
+    fn from(e: EncodeError) -> Self {
+        match e {
+            EncodeError::BufferTooSmall { needed } => Error::BufferTooSmall { needed },
+            EncodeError::FrameTooLarge { .. } => Error::MalformedBuffer(e.to_string()),
+        }
+    }
+}
+
+/// A frame with an unknown type or flags maps to [Error::MalformedBuffer].
+impl From<DecodeError> for Error {
+    fn from(e: DecodeError) -> Self {
+        match e {
+            DecodeError::Incomplete { needed } => Error::BufferTooSmall { needed },
+            DecodeError::Malformed(e) => e,
+            _ => Error::MalformedBuffer(e.to_string()),
+        }
+    }
+}
+
+impl From<DiffError> for Error {
+    fn from(e: DiffError) -> Self {
+        match e {
+            DiffError::ThresholdMismatch { older, newer } => Error::ThresholdMismatch {
+                expected: older,
+                actual: newer,
+            },
+            DiffError::NonMonotonic { older, newer } => Error::CountUnderflow {
+                count: newer,
+                removed: older,
+            },
+            DiffError::ThresholdExceeded { count, threshold } => {
+                Error::ExceedsThreshold { count, threshold }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frame::QuackFrame;
+    use crate::{PowerSumQuack, PowerSumQuackU32};
+
+    #[test]
+    fn test_from_frame_errors() {
+        let frame = QuackFrame {
+            seq: 1,
+            path_id: None,
+            quack: PowerSumQuackU32::new(10),
+        };
+        let mut buf = [0u8; 8];
+        let err: Error = frame.encode_into(&mut buf).unwrap_err().into();
+        assert_eq!(
+            err,
+            Error::BufferTooSmall {
+                needed: frame.encoded_len()
+            }
+        );
+
+        let bytes = frame.encode();
+        let err: Error = QuackFrame::decode(&bytes[..2]).unwrap_err().into();
+        assert_eq!(err, Error::BufferTooSmall { needed: 4 });
+        let err: Error = QuackFrame::decode(&[0; 8]).unwrap_err().into();
+        assert!(matches!(err, Error::MalformedBuffer(_)));
+
+        // the error from deserializing the quACK is passed through
+        let mut bytes = bytes;
+        bytes[8..16].fill(0);
+        let err: Error = QuackFrame::decode(&bytes).unwrap_err().into();
+        assert_eq!(err, Error::ZeroThreshold);
+    }
+
+    #[test]
+    fn test_from_bincode_error() {
+        let bytes = bincode::serialize(&PowerSumQuackU32::new(10)).unwrap();
+        let result = bincode::deserialize::<PowerSumQuackU32>(&bytes[..5]);
+        let err: Error = result.unwrap_err().into();
+        assert!(matches!(err, Error::MalformedBuffer(_)));
+    }
+
+    #[test]
+    fn test_from_diff_error() {
+        let err: Error = DiffError::NonMonotonic { older: 5, newer: 3 }.into();
+        assert_eq!(
+            err,
+            Error::CountUnderflow {
+                count: 3,
+                removed: 5
+            }
+        );
+        let err: Error = DiffError::ThresholdExceeded {
+            count: 11,
+            threshold: 10,
+        }
+        .into();
+        assert_eq!(
+            err,
+            Error::ExceedsThreshold {
+                count: 11,
+                threshold: 10
+            }
+        );
+    }
+}
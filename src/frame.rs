@@ -0,0 +1,342 @@
+//! A frame for carrying quACKs in-band, e.g., on a TCP stream or in a QUIC
+//! DATAGRAM frame.
+//!
+//! The frame starts with a 4-byte header: the frame type, a flags byte, and
+//! the length of the rest of the frame as a big-endian `u16`. The rest of the
+//! frame is the big-endian sequence number, the path identifier if the
+//! [FLAG_PATH_ID] flag is set, and the bincode-serialized quACK.
+use crate::precompute;
+use crate::{Error, PowerSumQuackU32};
+use std::fmt;
+
+/// The frame type of a quACK frame.
+pub const FRAME_TYPE_QUACK: u8 = 0x51;
+/// The flag that is set if the frame has a path identifier.
+pub const FLAG_PATH_ID: u8 = 0x01;
+/// The number of bytes before the length-prefixed part of the frame.
+pub const HEADER_LEN: usize = 4;
+
+/// A quACK with a sequence number and optional path identifier.
+#[derive(Clone, Debug)]
+pub struct QuackFrame {
+    /// The sequence number, so the other endpoint can discard stale quACKs.
+    pub seq: u32,
+    /// The path the quACK is for, if the endpoints use multiple paths.
+    pub path_id: Option<u8>,
+    /// The quACK.
+    pub quack: PowerSumQuackU32,
+}
+
+/// Why a frame could not be encoded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EncodeError {
+    /// The buffer is smaller than the encoded frame.
+    BufferTooSmall {
+        /// The length of the encoded frame.
+        needed: usize,
+    },
+    /// The frame is longer than the maximum length of a frame.
+    FrameTooLarge {
+        /// The length of the encoded frame.
+        len: usize,
+    },
+}
+
+/// Why a frame could not be decoded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The buffer only holds part of a frame. Try again with at least
+    /// `needed` bytes.
+    Incomplete {
+        /// The minimum number of bytes to decode the frame.
+        needed: usize,
+    },
+    /// The frame type is not [FRAME_TYPE_QUACK].
+    UnknownType(u8),
+    /// The frame has flags set that are not known.
+    UnknownFlags(u8),
+    /// The quACK could not be deserialized or does not fill the frame. A
+    /// quACK that does not fit in the frame is [Error::MalformedBuffer], not
+    /// [Error::BufferTooSmall], since more bytes would not help.
+    Malformed(Error),
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::BufferTooSmall { needed } => {
+                write!(f, "buffer too small, need {needed} bytes")
+            }
+            EncodeError::FrameTooLarge { len } => write!(f, "frame of {len} bytes too large"),
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Incomplete { needed } => {
+                write!(f, "incomplete frame, need {needed} bytes")
+            }
+            DecodeError::UnknownType(ty) => write!(f, "unknown frame type {ty:#04x}"),
+            DecodeError::UnknownFlags(flags) => write!(f, "unknown frame flags {flags:#04x}"),
+            DecodeError::Malformed(e) => write!(f, "malformed quACK: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DecodeError::Malformed(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl QuackFrame {
+    /// The number of bytes in the encoded frame.
+    pub fn encoded_len(&self) -> usize {
+        let quack_len = bincode::serialized_size(&self.quack).unwrap() as usize;
+        HEADER_LEN + 4 + usize::from(self.path_id.is_some()) + quack_len
+    }
+
+    /// Encodes the frame into the start of the buffer, returning the number of
+    /// bytes written. Does not allocate.
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        let len = self.encoded_len();
+        if len - HEADER_LEN > u16::MAX as usize {
+            return Err(EncodeError::FrameTooLarge { len });
+        }
+        if buf.len() < len {
+            return Err(EncodeError::BufferTooSmall { needed: len });
+        }
+        buf[0] = FRAME_TYPE_QUACK;
+        buf[1] = if self.path_id.is_some() {
+            FLAG_PATH_ID
+        } else {
+            0
+        };
+        buf[2..4].copy_from_slice(&((len - HEADER_LEN) as u16).to_be_bytes());
+        buf[4..8].copy_from_slice(&self.seq.to_be_bytes());
+        let mut offset = 8;
+        if let Some(path_id) = self.path_id {
+            buf[offset] = path_id;
+            offset += 1;
+        }
+        bincode::serialize_into(&mut buf[offset..len], &self.quack).unwrap();
+        Ok(len)
+    }
+
+    /// Encodes the frame into a new vector.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = vec![0; self.encoded_len()];
+        self.encode_into(&mut buf).unwrap();
+        buf
+    }
+
+    /// Decodes a frame from the start of the buffer, returning the frame and
+    /// the number of bytes consumed. The buffer may contain more bytes after
+    /// the frame, e.g., the next frame on a stream.
+    ///
+    /// The quACK is decoded with [PowerSumQuackU32::deserialize_bounded], so
+    /// a quACK with a threshold greater than the one set by
+    /// [crate::global_config_set_max_power_sum_threshold] is malformed.
+    pub fn decode(buf: &[u8]) -> Result<(QuackFrame, usize), DecodeError> {
+        if buf.len() < HEADER_LEN {
+            return Err(DecodeError::Incomplete { needed: HEADER_LEN });
+        }
+        if buf[0] != FRAME_TYPE_QUACK {
+            return Err(DecodeError::UnknownType(buf[0]));
+        }
+        let flags = buf[1];
+        if flags & !FLAG_PATH_ID != 0 {
+            return Err(DecodeError::UnknownFlags(flags));
+        }
+        let len = HEADER_LEN + usize::from(u16::from_be_bytes([buf[2], buf[3]]));
+        if buf.len() < len {
+            return Err(DecodeError::Incomplete { needed: len });
+        }
+
+        let mut body = &buf[HEADER_LEN..len];
+        let prefix_len = 4 + usize::from(flags & FLAG_PATH_ID != 0);
+        if body.len() < prefix_len {
+            return Err(DecodeError::Malformed(Error::MalformedBuffer(
+                "frame too short".to_string(),
+            )));
+        }
+        let seq = u32::from_be_bytes(body[..4].try_into().unwrap());
+        let path_id = (prefix_len == 5).then(|| body[4]);
+        body = &body[prefix_len..];
+        let (quack, quack_len) =
+            PowerSumQuackU32::deserialize_bounded(body, precompute::max_threshold()).map_err(
+                |e| match e {
+                    Error::BufferTooSmall { needed } => {
+                        DecodeError::Malformed(Error::MalformedBuffer(format!(
+                            "quACK of {needed} bytes does not fit in {} bytes",
+                            body.len()
+                        )))
+                    }
+                    e => DecodeError::Malformed(e),
+                },
+            )?;
+        if quack_len != body.len() {
+            return Err(DecodeError::Malformed(Error::MalformedBuffer(format!(
+                "{} trailing bytes",
+                body.len() - quack_len
+            ))));
+        }
+        let frame = QuackFrame {
+            seq,
+            path_id,
+            quack,
+        };
+        Ok((frame, len))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::PowerSumQuack;
+
+    const THRESHOLD: usize = 10;
+
+    fn frame(seq: u32, path_id: Option<u8>) -> QuackFrame {
+        let mut quack = PowerSumQuackU32::new(THRESHOLD);
+        for x in 1..=5 {
+            quack.insert(x * 1000);
+        }
+        QuackFrame {
+            seq,
+            path_id,
+            quack,
+        }
+    }
+
+    fn assert_same(actual: &QuackFrame, expected: &QuackFrame) {
+        assert_eq!(actual.seq, expected.seq);
+        assert_eq!(actual.path_id, expected.path_id);
+        assert_eq!(
+            bincode::serialize(&actual.quack).unwrap(),
+            bincode::serialize(&expected.quack).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for expected in [frame(7, None), frame(8, Some(3))] {
+            let mut buf = [0u8; 1500];
+            let len = expected.encode_into(&mut buf).unwrap();
+            assert_eq!(len, expected.encoded_len());
+            assert_eq!(&buf[..len], &expected.encode()[..]);
+            let (actual, consumed) = QuackFrame::decode(&buf[..len]).unwrap();
+            assert_eq!(consumed, len);
+            assert_same(&actual, &expected);
+        }
+    }
+
+    #[test]
+    fn test_header() {
+        let bytes = frame(0x01020304, Some(9)).encode();
+        assert_eq!(bytes[0], FRAME_TYPE_QUACK);
+        assert_eq!(bytes[1], FLAG_PATH_ID);
+        assert_eq!(
+            usize::from(u16::from_be_bytes([bytes[2], bytes[3]])),
+            bytes.len() - HEADER_LEN
+        );
+        assert_eq!(&bytes[4..9], &[1, 2, 3, 4, 9]);
+    }
+
+    #[test]
+    fn test_stream_of_frames() {
+        let frames = [frame(1, None), frame(2, Some(1)), frame(3, None)];
+        let stream = frames.iter().flat_map(|f| f.encode()).collect::<Vec<_>>();
+        let mut offset = 0;
+        for expected in &frames {
+            let (actual, consumed) = QuackFrame::decode(&stream[offset..]).unwrap();
+            assert_same(&actual, expected);
+            offset += consumed;
+        }
+        assert_eq!(offset, stream.len());
+    }
+
+    #[test]
+    fn test_truncated() {
+        let bytes = frame(1, Some(2)).encode();
+        for len in 0..bytes.len() {
+            match QuackFrame::decode(&bytes[..len]) {
+                Err(DecodeError::Incomplete { needed }) => {
+                    assert!(needed > len);
+                    assert!(needed <= bytes.len());
+                }
+                result => panic!("unexpected {:?} for {} bytes", result, len),
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_buffer_too_small() {
+        let frame = frame(1, None);
+        let mut buf = vec![0; frame.encoded_len() - 1];
+        assert_eq!(
+            frame.encode_into(&mut buf),
+            Err(EncodeError::BufferTooSmall {
+                needed: frame.encoded_len()
+            })
+        );
+    }
+
+    #[test]
+    fn test_malformed() {
+        let bytes = frame(1, None).encode();
+
+        let mut bad = bytes.clone();
+        bad[0] = 0;
+        assert!(matches!(
+            QuackFrame::decode(&bad),
+            Err(DecodeError::UnknownType(0))
+        ));
+
+        let mut bad = bytes.clone();
+        bad[1] = 0x80;
+        assert!(matches!(
+            QuackFrame::decode(&bad),
+            Err(DecodeError::UnknownFlags(0x80))
+        ));
+
+        // the length covers fewer bytes than the quACK
+        let mut bad = bytes.clone();
+        bad[3] -= 1;
+        assert!(matches!(
+            QuackFrame::decode(&bad),
+            Err(DecodeError::Malformed(Error::MalformedBuffer(_)))
+        ));
+
+        // the length covers more bytes than the quACK
+        let mut bad = bytes.clone();
+        bad[3] += 1;
+        bad.push(0);
+        assert_eq!(
+            QuackFrame::decode(&bad).unwrap_err(),
+            DecodeError::Malformed(Error::MalformedBuffer("1 trailing bytes".to_string()))
+        );
+
+        // the quACK has a zero threshold
+        let mut bad = bytes.clone();
+        bad[HEADER_LEN + 4..HEADER_LEN + 12].fill(0);
+        assert_eq!(
+            QuackFrame::decode(&bad).unwrap_err(),
+            DecodeError::Malformed(Error::ZeroThreshold)
+        );
+
+        // the length does not cover the sequence number
+        let bad = [FRAME_TYPE_QUACK, 0, 0, 2, 0, 0];
+        assert_eq!(
+            QuackFrame::decode(&bad).unwrap_err(),
+            DecodeError::Malformed(Error::MalformedBuffer("frame too short".to_string()))
+        );
+    }
+}
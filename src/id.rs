@@ -0,0 +1,184 @@
+//! Standard derivations of 32-bit packet identifiers.
+//!
+//! The sender and receiver of a quACK must derive the same identifier from
+//! the same packet. Every function in this module reads multi-byte fields in
+//! network byte order and hashes bytes in a fixed order, so the identifiers
+//! are the same across platforms. The keyed functions use SipHash-2-4 and
+//! take the low 32 bits of the 64-bit output, so other implementations can
+//! reproduce them with any reference SipHash-2-4 implementation.
+use siphasher::sip::SipHasher24;
+
+/// The maximum number of payload bytes hashed by [id_from_payload_prefix].
+pub const PAYLOAD_PREFIX_LEN: usize = 32;
+
+/// The maximum length of a QUIC connection ID.
+const MAX_DCID_LEN: usize = 20;
+
+/// A 128-bit SipHash key shared by the sender and receiver.
+///
+/// The first eight bytes are `k0` and the last eight bytes are `k1`, each in
+/// little-endian byte order as in the SipHash reference implementation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SipKey(pub [u8; 16]);
+
+impl SipKey {
+    fn hash(&self, bytes: &[u8]) -> u32 {
+        SipHasher24::new_with_key(&self.0).hash(bytes) as u32
+    }
+}
+
+/// Derives an identifier from an IPv4 header.
+///
+/// The high 16 bits are the identification field. The low 16 bits are the
+/// ones' complement sum, as in the header checksum, of the total length, the
+/// flags and fragment offset, the protocol, and the source and destination
+/// addresses. The TTL, header checksum, and DSCP/ECN fields are excluded
+/// because routers may modify them between the two endpoints.
+///
+/// Returns `None` if the bytes are not an IPv4 header.
+///
+/// # Examples
+///
+/// ```
+/// let header = [
+///     0x45, 0x00, 0x00, 0x3c, 0x1c, 0x46, 0x40, 0x00, 0x40, 0x06,
+///     0xb1, 0xe6, 0xac, 0x10, 0x0a, 0x63, 0xac, 0x10, 0x0a, 0x0c,
+/// ];
+/// assert_eq!(quack::id::id_from_ipv4(&header), Some(0x1c46acd2));
+/// ```
+pub fn id_from_ipv4(header: &[u8]) -> Option<u32> {
+    if header.len() < 20 || header[0] >> 4 != 4 || usize::from(header[0] & 0x0f) < 5 {
+        return None;
+    }
+    let word = |i: usize| u32::from(u16::from_be_bytes([header[i], header[i + 1]]));
+    let mut sum = word(2) + word(6) + u32::from(header[9]);
+    for i in (12..20).step_by(2) {
+        sum += word(i);
+    }
+    while sum >> 16 != 0 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    Some((word(4) << 16) | sum)
+}
+
+/// Derives an identifier from the first [PAYLOAD_PREFIX_LEN] bytes of a
+/// payload, or the entire payload if it is shorter.
+///
+/// The identifier is the low 32 bits of the SipHash-2-4 of the prefix.
+pub fn id_from_payload_prefix(payload: &[u8], key: SipKey) -> u32 {
+    key.hash(&payload[..payload.len().min(PAYLOAD_PREFIX_LEN)])
+}
+
+/// Derives an identifier from a QUIC destination connection ID and the full
+/// (decoded) packet number.
+///
+/// The identifier is the low 32 bits of the SipHash-2-4 of the one-byte
+/// connection ID length, the connection ID, and the packet number as eight
+/// big-endian bytes. Returns `None` if the connection ID is longer than the
+/// QUIC maximum of 20 bytes.
+pub fn id_from_quic_dcid_and_pn(dcid: &[u8], packet_number: u64, key: SipKey) -> Option<u32> {
+    if dcid.len() > MAX_DCID_LEN {
+        return None;
+    }
+    let mut buf = [0u8; 1 + MAX_DCID_LEN + 8];
+    let len = 1 + dcid.len() + 8;
+    buf[0] = dcid.len() as u8;
+    buf[1..1 + dcid.len()].copy_from_slice(dcid);
+    buf[1 + dcid.len()..len].copy_from_slice(&packet_number.to_be_bytes());
+    Some(key.hash(&buf[..len]))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The key in the SipHash reference test vectors.
+    const KEY: SipKey = SipKey([
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+        0x0f,
+    ]);
+
+    const IPV4_HEADER: [u8; 20] = [
+        0x45, 0x00, 0x00, 0x73, 0x00, 0x00, 0x40, 0x00, 0x40, 0x11, 0xb8, 0x61, 0xc0, 0xa8, 0x00,
+        0x01, 0xc0, 0xa8, 0x00, 0xc7,
+    ];
+
+    #[test]
+    fn test_ipv4_vectors() {
+        assert_eq!(id_from_ipv4(&IPV4_HEADER), Some(0x0000c29d));
+        let header = [
+            0x45, 0x00, 0x00, 0x3c, 0x1c, 0x46, 0x40, 0x00, 0x40, 0x06, 0xb1, 0xe6, 0xac, 0x10,
+            0x0a, 0x63, 0xac, 0x10, 0x0a, 0x0c,
+        ];
+        assert_eq!(id_from_ipv4(&header), Some(0x1c46acd2));
+    }
+
+    #[test]
+    fn test_ipv4_ignores_mutable_fields() {
+        let mut header = IPV4_HEADER;
+        header[1] = 0x02; // ECN
+        header[8] -= 1; // TTL
+        header[10..12].copy_from_slice(&[0xb9, 0x61]); // checksum
+        assert_eq!(id_from_ipv4(&header), id_from_ipv4(&IPV4_HEADER));
+
+        // options after the fixed header are ignored
+        let mut with_options = IPV4_HEADER.to_vec();
+        with_options.extend_from_slice(&[0x01, 0x01, 0x01, 0x00]);
+        assert_eq!(id_from_ipv4(&with_options), id_from_ipv4(&IPV4_HEADER));
+    }
+
+    #[test]
+    fn test_ipv4_invalid() {
+        assert_eq!(id_from_ipv4(&IPV4_HEADER[..19]), None);
+        let mut header = IPV4_HEADER;
+        header[0] = 0x65; // version 6
+        assert_eq!(id_from_ipv4(&header), None);
+        header[0] = 0x44; // IHL < 5
+        assert_eq!(id_from_ipv4(&header), None);
+    }
+
+    #[test]
+    fn test_siphash_reference_vector() {
+        // SipHash-2-4 of the empty message is 0x726fdb47dd0e0e31.
+        assert_eq!(id_from_payload_prefix(&[], KEY), 0xdd0e0e31);
+    }
+
+    #[test]
+    fn test_payload_prefix_vectors() {
+        assert_eq!(id_from_payload_prefix(b"hello", KEY), 0x5767df81);
+        let payload = (0..64).collect::<Vec<u8>>();
+        assert_eq!(id_from_payload_prefix(&payload, KEY), 0x72f27cce);
+        assert_eq!(
+            id_from_payload_prefix(&payload[..PAYLOAD_PREFIX_LEN], KEY),
+            0x72f27cce
+        );
+        assert_ne!(
+            id_from_payload_prefix(b"hello", SipKey::default()),
+            0x5767df81
+        );
+    }
+
+    #[test]
+    fn test_quic_vectors() {
+        let dcid = [0x83, 0x94, 0xc8, 0xf0, 0x3e, 0x51, 0x57, 0x08];
+        assert_eq!(
+            id_from_quic_dcid_and_pn(&dcid, 0x1234, KEY),
+            Some(0x06a27af2)
+        );
+        assert_eq!(
+            id_from_quic_dcid_and_pn(&[], 0, SipKey::default()),
+            Some(0x0c8f0cef)
+        );
+        assert_eq!(id_from_quic_dcid_and_pn(&[0; 21], 0, KEY), None);
+
+        // the longest connection ID fills the whole buffer
+        let dcid = [0xab; 20];
+        let mut bytes = vec![20];
+        bytes.extend_from_slice(&dcid);
+        bytes.extend_from_slice(&u64::MAX.to_be_bytes());
+        assert_eq!(
+            id_from_quic_dcid_and_pn(&dcid, u64::MAX, KEY),
+            Some(KEY.hash(&bytes))
+        );
+    }
+}
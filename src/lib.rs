@@ -1,5 +1,4 @@
-#![feature(doc_cfg)]
-#![feature(const_trait_impl)]
+#![cfg_attr(docsrs, feature(doc_cfg))]
 
 //! The _quACK_ is a data structure for being able to refer to and efficiently
 //! acknowledge a set of opaque packets seen by a network intermediary. The
@@ -35,8 +34,33 @@ pub mod arithmetic {
     }
 }
 
+mod error;
+pub use error::{Error, Result};
+
 mod power_sum;
-pub use power_sum::{PowerSumQuack, PowerSumQuackU32};
+#[cfg(feature = "rayon")]
+pub use power_sum::PARALLEL_DECODE_MIN_LOG_LEN;
+pub use power_sum::{DecodedDiff, PowerSumQuack, PowerSumQuackU32};
+
+pub mod builder;
+pub use builder::QuackBuilder;
+
+mod checksum;
+pub mod delta;
+pub mod frame;
+pub mod id;
+pub mod multipath;
+pub mod negotiate;
+pub mod prelude;
+pub mod receiver;
+pub mod sender;
+pub mod sync;
+mod varint;
+pub mod wire;
+
+cfg_sniffer! {
+    pub mod sniffer;
+}
 
 cfg_strawmen! {
     mod strawmen;
@@ -6,7 +6,7 @@ macro_rules! cfg_montgomery {
     ($($item:item)*) => {
         $(
             #[cfg(any(feature = "montgomery", doc))]
-            #[doc(cfg(feature = "montgomery"))]
+            #[cfg_attr(docsrs, doc(cfg(feature = "montgomery")))]
             $item
         )*
     }
@@ -18,7 +18,7 @@ macro_rules! cfg_power_table {
     ($($item:item)*) => {
         $(
             #[cfg(any(feature = "power_table", doc))]
-            #[doc(cfg(feature = "power_table"))]
+            #[cfg_attr(docsrs, doc(cfg(feature = "power_table")))]
             $item
         )*
     }
@@ -30,7 +30,7 @@ macro_rules! cfg_libpari {
     ($($item:item)*) => {
         $(
             #[cfg(any(feature = "libpari", doc))]
-            #[doc(cfg(feature = "libpari"))]
+            #[cfg_attr(docsrs, doc(cfg(feature = "libpari")))]
             $item
         )*
     }
@@ -42,8 +42,30 @@ macro_rules! cfg_strawmen {
     ($($item:item)*) => {
         $(
             #[cfg(any(feature = "strawmen", doc))]
-            #[doc(cfg(feature = "strawmen"))]
+            #[cfg_attr(docsrs, doc(cfg(feature = "strawmen")))]
             $item
         )*
     }
 }
+
+/// Enables code for capturing packets with libpcap.
+/// Unlike the other features, this feature pulls in a dependency, so its docs
+/// are only generated when the feature is enabled.
+macro_rules! cfg_sniffer {
+    ($($item:item)*) => {
+        $(
+            #[cfg(feature = "sniffer")]
+            #[cfg_attr(docsrs, doc(cfg(feature = "sniffer")))]
+            $item
+        )*
+    }
+}
+
+/// Emits a `tracing` event at the given level if the `tracing` feature is
+/// enabled, and otherwise compiles to nothing.
+macro_rules! trace_event {
+    ($level:ident, $($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::event!(tracing::Level::$level, $($arg)*);
+    };
+}
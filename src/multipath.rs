@@ -0,0 +1,394 @@
+//! A set of power sum quACKs for a sender that stripes traffic over multiple
+//! paths, keyed by path identifier.
+use crate::{Error, PowerSumQuack, PowerSumQuackU32};
+use std::collections::{BTreeMap, HashMap};
+
+/// The number of distinct path identifiers.
+const NUM_PATHS: usize = 1 << u8::BITS;
+
+/// One 32-bit power sum quACK per path.
+///
+/// Every quACK in the set has the same threshold. The quACK for a path is
+/// created the first time an element is inserted on that path.
+#[derive(Clone, Debug)]
+pub struct QuackSet {
+    threshold: usize,
+    quacks: BTreeMap<u8, PowerSumQuackU32>,
+}
+
+/// The paths that only one side of [QuackSet::sub_assign] has a quACK for.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UnmatchedPaths {
+    /// Paths only in the left-hand side. Their quACKs are left unchanged, as
+    /// if the right-hand side were an empty quACK.
+    pub lhs_only: Vec<u8>,
+    /// Paths only in the right-hand side. Their quACKs are ignored.
+    pub rhs_only: Vec<u8>,
+}
+
+impl UnmatchedPaths {
+    /// Whether both sides have quACKs for exactly the same paths.
+    pub fn is_empty(&self) -> bool {
+        self.lhs_only.is_empty() && self.rhs_only.is_empty()
+    }
+}
+
+impl QuackSet {
+    /// Creates a new, empty quACK set where each path can decode at most
+    /// `threshold` number of elements.
+    pub fn new(threshold: usize) -> Self {
+        Self {
+            threshold,
+            quacks: BTreeMap::new(),
+        }
+    }
+
+    /// The maximum number of elements that can be decoded on each path.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// The total number of elements represented by the quACKs on all paths.
+    pub fn count(&self) -> u32 {
+        self.quacks
+            .values()
+            .fold(0, |count, quack| count.wrapping_add(quack.count()))
+    }
+
+    /// The identifiers of the paths with a quACK, in increasing order.
+    pub fn paths(&self) -> impl Iterator<Item = u8> + '_ {
+        self.quacks.keys().copied()
+    }
+
+    /// The quACK for a path, if any element has been inserted on it.
+    pub fn get(&self, path_id: u8) -> Option<&PowerSumQuackU32> {
+        self.quacks.get(&path_id)
+    }
+
+    /// Insert an element in the quACK for a path.
+    pub fn insert(&mut self, path_id: u8, value: u32) {
+        let threshold = self.threshold;
+        self.quacks
+            .entry(path_id)
+            .or_insert_with(|| PowerSumQuackU32::new(threshold))
+            .insert(value);
+    }
+
+    /// Subtracts the quACK for each path in `rhs` from the quACK for the same
+    /// path in this set, and reports the paths that are not in both sets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sets do not have the same threshold.
+    pub fn sub_assign(&mut self, rhs: &QuackSet) -> UnmatchedPaths {
+        match self.try_sub_assign(rhs) {
+            Ok(unmatched) => unmatched,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Similar to [QuackSet::sub_assign] but returns
+    /// [Error::ThresholdMismatch] instead of panicking if the sets have
+    /// different thresholds. The set is unchanged on error.
+    pub fn try_sub_assign(&mut self, rhs: &QuackSet) -> Result<UnmatchedPaths, Error> {
+        if self.threshold != rhs.threshold {
+            return Err(Error::ThresholdMismatch {
+                expected: self.threshold,
+                actual: rhs.threshold,
+            });
+        }
+        let mut unmatched = UnmatchedPaths::default();
+        for (path_id, quack) in &rhs.quacks {
+            match self.quacks.get_mut(path_id) {
+                Some(lhs) => lhs.sub_assign(quack.clone()),
+                None => unmatched.rhs_only.push(*path_id),
+            }
+        }
+        unmatched.lhs_only = self
+            .quacks
+            .keys()
+            .filter(|path_id| !rhs.quacks.contains_key(path_id))
+            .copied()
+            .collect();
+        Ok(unmatched)
+    }
+
+    /// Decodes the quACK for each path with the log of elements sent on that
+    /// path. The missing elements on a path are `None` if there are more than
+    /// the threshold. Paths without a log are decoded with an empty log.
+    pub fn decode_with_logs(&self, logs: &HashMap<u8, Vec<u32>>) -> BTreeMap<u8, Option<Vec<u32>>> {
+        self.quacks
+            .iter()
+            .map(|(&path_id, quack)| {
+                let missing = if quack.count() == 0 {
+                    Some(vec![])
+                } else if !quack.can_decode() {
+                    None
+                } else {
+                    let log = logs.get(&path_id).map(|log| &log[..]).unwrap_or(&[]);
+                    Some(quack.decode_with_log(log))
+                };
+                (path_id, missing)
+            })
+            .collect()
+    }
+
+    /// Serializes the set into a single buffer: the number of paths as a
+    /// little-endian `u16`, since there can be 256 paths, followed by each
+    /// path identifier and its bincode-serialized quACK.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes = (self.quacks.len() as u16).to_le_bytes().to_vec();
+        for (&path_id, quack) in &self.quacks {
+            bytes.push(path_id);
+            bincode::serialize_into(&mut bytes, quack).unwrap();
+        }
+        bytes
+    }
+
+    /// Deserializes a set serialized with [QuackSet::serialize].
+    ///
+    /// Returns [Error::BufferTooSmall] if the buffer is truncated,
+    /// [Error::ThresholdMismatch] if a quACK does not have the given
+    /// threshold, and [Error::MalformedBuffer] if a path appears more than
+    /// once or the buffer is otherwise malformed.
+    pub fn deserialize(bytes: &[u8], threshold: usize) -> Result<Self, Error> {
+        let num_paths = bytes.get(..2).ok_or(Error::BufferTooSmall { needed: 2 })?;
+        let num_paths = u16::from_le_bytes([num_paths[0], num_paths[1]]);
+        if usize::from(num_paths) > NUM_PATHS {
+            return Err(Error::MalformedBuffer(format!(
+                "{} paths exceeds the maximum {}",
+                num_paths, NUM_PATHS
+            )));
+        }
+        let mut set = Self::new(threshold);
+        let mut offset = 2;
+        for _ in 0..num_paths {
+            let path_id = *bytes
+                .get(offset)
+                .ok_or(Error::BufferTooSmall { needed: offset + 1 })?;
+            offset += 1;
+            let (quack, len) = PowerSumQuackU32::deserialize_bounded(&bytes[offset..], threshold)
+                .map_err(|e| match e {
+                Error::BufferTooSmall { needed } => Error::BufferTooSmall {
+                    needed: offset.saturating_add(needed),
+                },
+                e => e,
+            })?;
+            offset += len;
+            if quack.threshold() != threshold {
+                return Err(Error::ThresholdMismatch {
+                    expected: threshold,
+                    actual: quack.threshold(),
+                });
+            }
+            if set.quacks.insert(path_id, quack).is_some() {
+                return Err(Error::MalformedBuffer(format!(
+                    "duplicate path {}",
+                    path_id
+                )));
+            }
+        }
+        if offset != bytes.len() {
+            return Err(Error::MalformedBuffer(format!(
+                "{} trailing bytes",
+                bytes.len() - offset
+            )));
+        }
+        Ok(set)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const THRESHOLD: usize = 20;
+    const MTU: usize = 1500;
+
+    /// Stripes the elements round-robin over the paths, returning the set
+    /// and the log of elements sent on each path.
+    fn stripe(paths: &[u8], elems: &[u32]) -> (QuackSet, HashMap<u8, Vec<u32>>) {
+        let mut set = QuackSet::new(THRESHOLD);
+        let mut logs: HashMap<u8, Vec<u32>> = HashMap::new();
+        for (i, &elem) in elems.iter().enumerate() {
+            let path_id = paths[i % paths.len()];
+            set.insert(path_id, elem);
+            logs.entry(path_id).or_default().push(elem);
+        }
+        (set, logs)
+    }
+
+    #[test]
+    fn test_insert_per_path() {
+        let (set, logs) = stripe(&[3, 1], &(1..=10).collect::<Vec<_>>());
+        assert_eq!(set.paths().collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(set.count(), 10);
+        assert_eq!(set.get(3).unwrap().count(), 5);
+        assert_eq!(set.get(1).unwrap().last_value(), Some(10));
+        assert!(set.get(2).is_none());
+        assert_eq!(logs[&3], vec![1, 3, 5, 7, 9]);
+    }
+
+    #[test]
+    fn test_decode_per_path() {
+        let elems = (1..=30).collect::<Vec<u32>>();
+        let (mut sent, logs) = stripe(&[0, 1, 2], &elems);
+        let received = elems
+            .iter()
+            .copied()
+            .filter(|x| x % 7 != 0)
+            .collect::<Vec<_>>();
+        let mut recv = QuackSet::new(THRESHOLD);
+        for &x in &received {
+            recv.insert(((x - 1) % 3) as u8, x);
+        }
+        assert!(sent.sub_assign(&recv).is_empty());
+
+        let missing = sent.decode_with_logs(&logs);
+        assert_eq!(missing[&0], Some(vec![7, 28]));
+        assert_eq!(missing[&1], Some(vec![14]));
+        assert_eq!(missing[&2], Some(vec![21]));
+    }
+
+    #[test]
+    fn test_asymmetric_paths() {
+        let mut sent = QuackSet::new(THRESHOLD);
+        sent.insert(0, 1);
+        sent.insert(0, 2);
+        sent.insert(1, 3);
+        let mut recv = QuackSet::new(THRESHOLD);
+        recv.insert(0, 1);
+        recv.insert(2, 4);
+
+        let unmatched = sent.sub_assign(&recv);
+        assert_eq!(unmatched.lhs_only, vec![1]);
+        assert_eq!(unmatched.rhs_only, vec![2]);
+        assert!(!unmatched.is_empty());
+
+        let logs = HashMap::from([(0, vec![1, 2]), (1, vec![3])]);
+        let missing = sent.decode_with_logs(&logs);
+        assert_eq!(missing.len(), 2);
+        assert_eq!(missing[&0], Some(vec![2]));
+        assert_eq!(missing[&1], Some(vec![3]));
+    }
+
+    #[test]
+    fn test_decode_over_threshold() {
+        let elems = (1..=(THRESHOLD as u32 + 1)).collect::<Vec<_>>();
+        let (set, logs) = stripe(&[5], &elems);
+        assert_eq!(set.decode_with_logs(&logs)[&5], None);
+    }
+
+    #[test]
+    fn test_serialize_round_trip_within_mtu() {
+        let elems = (1..=1000).collect::<Vec<u32>>();
+        let (set, _) = stripe(&[0, 1, 2, 3, 4, 5, 6, 7], &elems);
+        let bytes = set.serialize();
+        assert!(bytes.len() <= MTU, "{} bytes", bytes.len());
+        assert_eq!(&bytes[..2], &[8, 0]);
+        let deserialized = QuackSet::deserialize(&bytes, THRESHOLD).unwrap();
+        assert_eq!(
+            deserialized.paths().collect::<Vec<_>>(),
+            (0..8).collect::<Vec<_>>()
+        );
+        assert_eq!(deserialized.count(), 1000);
+        assert_eq!(deserialized.serialize(), bytes);
+
+        let empty = QuackSet::new(THRESHOLD);
+        let bytes = empty.serialize();
+        assert_eq!(bytes, vec![0, 0]);
+        assert_eq!(QuackSet::deserialize(&bytes, THRESHOLD).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_serialize_all_paths() {
+        let mut set = QuackSet::new(1);
+        (0..=u8::MAX).for_each(|path_id| set.insert(path_id, path_id.into()));
+        let bytes = set.serialize();
+        assert_eq!(&bytes[..2], &[0, 1]);
+        let deserialized = QuackSet::deserialize(&bytes, 1).unwrap();
+        assert_eq!(deserialized.paths().count(), NUM_PATHS);
+        assert_eq!(deserialized.get(u8::MAX).unwrap().last_value(), Some(255));
+        assert_eq!(deserialized.serialize(), bytes);
+
+        // more paths than there are path identifiers
+        let mut bytes = bytes;
+        bytes[..2].copy_from_slice(&(NUM_PATHS as u16 + 1).to_le_bytes());
+        assert!(matches!(
+            QuackSet::deserialize(&bytes, 1),
+            Err(Error::MalformedBuffer(_))
+        ));
+    }
+
+    #[test]
+    fn test_try_sub_assign_threshold_mismatch() {
+        let (mut sent, _) = stripe(&[0, 1], &[1, 2, 3]);
+        let before = sent.serialize();
+        let mut recv = QuackSet::new(THRESHOLD + 1);
+        recv.insert(0, 1);
+        assert_eq!(
+            sent.try_sub_assign(&recv),
+            Err(Error::ThresholdMismatch {
+                expected: THRESHOLD,
+                actual: THRESHOLD + 1,
+            })
+        );
+        assert_eq!(sent.serialize(), before);
+        let recv = QuackSet::new(THRESHOLD);
+        assert_eq!(sent.try_sub_assign(&recv).unwrap().lhs_only, vec![0, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "threshold")]
+    fn test_sub_assign_threshold_mismatch_panics() {
+        let mut sent = QuackSet::new(THRESHOLD);
+        sent.sub_assign(&QuackSet::new(THRESHOLD + 1));
+    }
+
+    #[test]
+    fn test_deserialize_malformed() {
+        let (set, _) = stripe(&[0, 1], &[1, 2, 3]);
+        let bytes = set.serialize();
+        assert_eq!(
+            QuackSet::deserialize(&[], THRESHOLD).unwrap_err(),
+            Error::BufferTooSmall { needed: 2 }
+        );
+        assert_eq!(
+            QuackSet::deserialize(&bytes[..2], THRESHOLD).unwrap_err(),
+            Error::BufferTooSmall { needed: 3 }
+        );
+        assert_eq!(
+            QuackSet::deserialize(&bytes[..bytes.len() - 1], THRESHOLD).unwrap_err(),
+            Error::BufferTooSmall {
+                needed: bytes.len()
+            }
+        );
+        assert_eq!(
+            QuackSet::deserialize(&bytes, THRESHOLD + 1).unwrap_err(),
+            Error::ThresholdMismatch {
+                expected: THRESHOLD + 1,
+                actual: THRESHOLD,
+            }
+        );
+        assert!(matches!(
+            QuackSet::deserialize(&bytes, THRESHOLD - 1),
+            Err(Error::MalformedBuffer(_))
+        ));
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            QuackSet::deserialize(&trailing, THRESHOLD).unwrap_err(),
+            Error::MalformedBuffer("1 trailing bytes".to_string())
+        );
+
+        // both quACKs tagged with path 0
+        let mut duplicate = bytes.clone();
+        let second = 3 + bincode::serialized_size(set.get(0).unwrap()).unwrap() as usize;
+        duplicate[second] = 0;
+        assert_eq!(
+            QuackSet::deserialize(&duplicate, THRESHOLD).unwrap_err(),
+            Error::MalformedBuffer("duplicate path 0".to_string())
+        );
+    }
+}
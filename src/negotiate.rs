@@ -0,0 +1,364 @@
+//! Negotiation of quACK parameters between the two endpoints.
+//!
+//! The endpoint that will decode quACKs sends an [Message::Offer] listing the
+//! parameters it supports in order of preference. The other endpoint picks
+//! the first offered parameters it also supports with [resolve] and replies
+//! with an [Message::Accept]. Both endpoints then construct their quACKs from
+//! the accepted parameters with [KindParams::build].
+use crate::{Error, PowerSumQuack, PowerSumQuackU32, QuackBuilder};
+use serde::{Deserialize, Serialize};
+
+/// The current version of the quACK wire format.
+pub const WIRE_VERSION: u8 = 1;
+
+/// A quACK implementation.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuackKind {
+    /// Power sum quACKs with standard modular arithmetic.
+    PowerSum,
+    /// Power sum quACKs with Montgomery multiplication.
+    Montgomery,
+    /// Power sum quACKs with a precomputed power table.
+    PowerTable,
+}
+
+/// How the endpoints derive packet identifiers, see [crate::id].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IdStrategy {
+    /// [crate::id::id_from_ipv4].
+    Ipv4,
+    /// [crate::id::id_from_payload_prefix].
+    PayloadPrefix,
+    /// [crate::id::id_from_quic_dcid_and_pn].
+    QuicDcidAndPn,
+}
+
+/// A configuration of quACK parameters.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KindParams {
+    /// The quACK implementation.
+    pub kind: QuackKind,
+    /// The number of bits in each element.
+    pub bits: u8,
+    /// The threshold number of missing elements. In an offer, this is the
+    /// maximum threshold the endpoint supports.
+    pub threshold: u16,
+    /// How packet identifiers are derived.
+    pub id_strategy: IdStrategy,
+    /// The version of the quACK wire format.
+    pub version: u8,
+}
+
+/// A handshake message.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    /// The supported parameters, in order of preference.
+    Offer {
+        /// The supported parameters.
+        kinds: Vec<KindParams>,
+    },
+    /// The parameters picked from an offer.
+    Accept {
+        /// The picked parameters.
+        chosen: KindParams,
+    },
+}
+
+impl Message {
+    /// Serializes the message with `bincode`.
+    pub fn serialize(&self) -> Vec<u8> {
+        bincode::serialize(self).unwrap()
+    }
+
+    /// Deserializes a message serialized with [Message::serialize]. Returns
+    /// [Error::MalformedBuffer] if the bytes are truncated, have trailing
+    /// bytes, or contain an unknown quACK kind or identifier strategy.
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, Error> {
+        let mut rest = bytes;
+        let message = bincode::deserialize_from(&mut rest)?;
+        if !rest.is_empty() {
+            return Err(Error::MalformedBuffer(format!(
+                "{} trailing bytes",
+                rest.len()
+            )));
+        }
+        Ok(message)
+    }
+}
+
+/// A quACK type that can be constructed from negotiated parameters.
+pub trait Negotiable: PowerSumQuack + Sized {
+    /// The quACK implementation.
+    const KIND: QuackKind;
+    /// The number of bits in each element.
+    const BITS: u8;
+
+    /// The parameters for this quACK type at the current wire version.
+    fn params(threshold: u16, id_strategy: IdStrategy) -> KindParams {
+        KindParams {
+            kind: Self::KIND,
+            bits: Self::BITS,
+            threshold,
+            id_strategy,
+            version: WIRE_VERSION,
+        }
+    }
+}
+
+impl Negotiable for PowerSumQuackU32 {
+    const KIND: QuackKind = QuackKind::PowerSum;
+    const BITS: u8 = 32;
+}
+
+cfg_montgomery! {
+    impl Negotiable for crate::PowerSumQuackU64 {
+        const KIND: QuackKind = QuackKind::PowerSum;
+        const BITS: u8 = 64;
+    }
+
+    impl Negotiable for crate::MontgomeryQuack {
+        const KIND: QuackKind = QuackKind::Montgomery;
+        const BITS: u8 = 64;
+    }
+}
+
+cfg_power_table! {
+    impl Negotiable for crate::PowerSumQuackU16 {
+        const KIND: QuackKind = QuackKind::PowerSum;
+        const BITS: u8 = 16;
+    }
+
+    impl Negotiable for crate::PowerTableQuack {
+        const KIND: QuackKind = QuackKind::PowerTable;
+        const BITS: u8 = 16;
+    }
+}
+
+impl KindParams {
+    /// Constructs an empty quACK of type `Q` with the negotiated threshold.
+    /// Returns `None` if `Q` is not the negotiated kind and element width, the
+    /// wire version is unsupported, or [QuackBuilder::build] fails for the
+    /// threshold.
+    pub fn build<Q: Negotiable>(&self) -> Option<Q> {
+        self.try_build().ok()
+    }
+
+    /// Similar to [KindParams::build] but returns why the quACK could not be
+    /// constructed.
+    pub fn try_build<Q: Negotiable>(&self) -> Result<Q, Error> {
+        if self.kind != Q::KIND || self.bits != Q::BITS {
+            return Err(Error::TypeMismatch {
+                kind: self.kind,
+                bits: self.bits,
+            });
+        }
+        if self.version != WIRE_VERSION {
+            return Err(Error::UnsupportedVersion(self.version));
+        }
+        QuackBuilder::new()
+            .threshold(self.threshold as usize)
+            .build()
+    }
+}
+
+/// Picks the first offered parameters that are also supported locally. The
+/// kind, element width, identifier strategy, and wire version must match, and
+/// the threshold is the smaller of the two thresholds. Returns `None` if there
+/// are no mutually supported parameters.
+pub fn resolve(offered: &[KindParams], supported: &[KindParams]) -> Option<KindParams> {
+    offered.iter().find_map(|offer| {
+        supported
+            .iter()
+            .filter(|local| {
+                local.kind == offer.kind
+                    && local.bits == offer.bits
+                    && local.id_strategy == offer.id_strategy
+                    && local.version == offer.version
+                    && offer.version == WIRE_VERSION
+            })
+            .map(|local| KindParams {
+                threshold: local.threshold.min(offer.threshold),
+                ..*offer
+            })
+            .find(|params| params.threshold > 0)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const U16: KindParams = KindParams {
+        kind: QuackKind::PowerTable,
+        bits: 16,
+        threshold: 20,
+        id_strategy: IdStrategy::Ipv4,
+        version: WIRE_VERSION,
+    };
+    const U32: KindParams = KindParams {
+        kind: QuackKind::PowerSum,
+        bits: 32,
+        threshold: 20,
+        id_strategy: IdStrategy::Ipv4,
+        version: WIRE_VERSION,
+    };
+
+    #[test]
+    fn test_message_round_trip() {
+        let offer = Message::Offer {
+            kinds: vec![U16, U32],
+        };
+        assert_eq!(Message::deserialize(&offer.serialize()).unwrap(), offer);
+        let accept = Message::Accept { chosen: U32 };
+        assert_eq!(Message::deserialize(&accept.serialize()).unwrap(), accept);
+    }
+
+    #[test]
+    fn test_malformed_messages() {
+        let bytes = Message::Offer {
+            kinds: vec![U16, U32],
+        }
+        .serialize();
+        let malformed =
+            |bytes: &[u8]| matches!(Message::deserialize(bytes), Err(Error::MalformedBuffer(_)));
+        for len in 0..bytes.len() {
+            assert!(malformed(&bytes[..len]), "{} bytes", len);
+        }
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            Message::deserialize(&trailing).unwrap_err(),
+            Error::MalformedBuffer("1 trailing bytes".to_string())
+        );
+
+        // unknown message type
+        let mut unknown = bytes.clone();
+        unknown[0] = 2;
+        assert!(malformed(&unknown));
+
+        // unknown quACK kind in the first offered parameters
+        let mut unknown = bytes.clone();
+        unknown[12] = 3;
+        assert!(malformed(&unknown));
+
+        // a huge number of offered parameters
+        let mut huge = bytes;
+        huge[4..12].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(malformed(&huge));
+    }
+
+    #[test]
+    fn test_resolve_prefers_offer_order() {
+        let supported = [U32, U16];
+        assert_eq!(resolve(&[U16, U32], &supported), Some(U16));
+        assert_eq!(resolve(&[U32, U16], &supported), Some(U32));
+    }
+
+    #[test]
+    fn test_resolve_picks_smaller_threshold() {
+        let supported = KindParams {
+            threshold: 10,
+            ..U32
+        };
+        let chosen = resolve(&[U32], &[supported]).unwrap();
+        assert_eq!(chosen.threshold, 10);
+    }
+
+    #[test]
+    fn test_resolve_no_match() {
+        assert_eq!(resolve(&[U16], &[U32]), None);
+        assert_eq!(resolve(&[], &[U32]), None);
+        let payload = KindParams {
+            id_strategy: IdStrategy::PayloadPrefix,
+            ..U32
+        };
+        assert_eq!(resolve(&[payload], &[U32]), None);
+        let future = KindParams {
+            version: WIRE_VERSION + 1,
+            ..U32
+        };
+        assert_eq!(resolve(&[future], &[future]), None);
+        let zero = KindParams {
+            threshold: 0,
+            ..U32
+        };
+        assert_eq!(resolve(&[U32], &[zero]), None);
+    }
+
+    #[test]
+    fn test_build() {
+        let quack: PowerSumQuackU32 = U32.build().unwrap();
+        assert_eq!(quack.threshold(), 20);
+        assert!(U16.build::<PowerSumQuackU32>().is_none());
+        assert!(KindParams { bits: 64, ..U32 }
+            .build::<PowerSumQuackU32>()
+            .is_none());
+        assert_eq!(PowerSumQuackU32::params(20, IdStrategy::Ipv4), U32);
+    }
+
+    #[test]
+    fn test_try_build_errors() {
+        assert_eq!(
+            U16.try_build::<PowerSumQuackU32>().unwrap_err(),
+            Error::TypeMismatch {
+                kind: QuackKind::PowerTable,
+                bits: 16
+            }
+        );
+        let future = KindParams {
+            version: WIRE_VERSION + 1,
+            ..U32
+        };
+        assert_eq!(
+            future.try_build::<PowerSumQuackU32>().unwrap_err(),
+            Error::UnsupportedVersion(WIRE_VERSION + 1)
+        );
+        let zero = KindParams {
+            threshold: 0,
+            ..U32
+        };
+        assert_eq!(
+            zero.try_build::<PowerSumQuackU32>().unwrap_err(),
+            Error::ZeroThreshold
+        );
+    }
+
+    #[test]
+    fn test_negotiate_and_decode() {
+        // The sender offers two configurations and the receiver only supports
+        // 32-bit power sums with a smaller threshold.
+        let offer = Message::Offer {
+            kinds: vec![U16, PowerSumQuackU32::params(20, IdStrategy::Ipv4)],
+        }
+        .serialize();
+        let accept = match Message::deserialize(&offer).unwrap() {
+            Message::Offer { kinds } => {
+                let supported = [PowerSumQuackU32::params(10, IdStrategy::Ipv4)];
+                let chosen = resolve(&kinds, &supported).unwrap();
+                Message::Accept { chosen }.serialize()
+            }
+            _ => unreachable!(),
+        };
+        let chosen = match Message::deserialize(&accept).unwrap() {
+            Message::Accept { chosen } => chosen,
+            _ => unreachable!(),
+        };
+        assert_eq!(chosen.threshold, 10);
+
+        let mut sender: PowerSumQuackU32 = chosen.build().unwrap();
+        let mut receiver: PowerSumQuackU32 = chosen.build().unwrap();
+        let log = (1..=50).collect::<Vec<u32>>();
+        for &x in &log {
+            sender.insert(x);
+            if x % 10 != 0 {
+                receiver.insert(x);
+            }
+        }
+        let bytes = bincode::serialize(&receiver).unwrap();
+        let receiver: PowerSumQuackU32 = bincode::deserialize(&bytes).unwrap();
+        sender.sub_assign(receiver);
+        let mut missing = sender.decode_with_log(&log);
+        missing.sort();
+        assert_eq!(missing, vec![10, 20, 30, 40, 50]);
+    }
+}
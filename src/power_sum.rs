@@ -1,7 +1,44 @@
 use crate::arithmetic::{self, CoefficientVector, ModularArithmetic, ModularInteger};
-use crate::precompute::INVERSE_TABLE_U32;
+use crate::checksum;
+use crate::error::Error;
+use crate::precompute::{self, INVERSE_TABLE_U32};
+use crate::varint;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt::Debug;
+use std::hash::Hash;
+
+/// The number of elements whose powers [PowerSumQuackU32::insert_batch]
+/// computes at once.
+const INSERT_BATCH_LANES: usize = 8;
+
+/// The tags of the last value in a delta from
+/// [PowerSumQuackU32::serialize_delta].
+const DELTA_LAST_VALUE_UNCHANGED: u8 = 0;
+const DELTA_LAST_VALUE_SOME: u8 = 1;
+const DELTA_LAST_VALUE_NONE: u8 = 2;
+
+/// The minimum length of a log that `decode_with_log` splits across threads
+/// with the `rayon` feature. Shorter logs are not worth the synchronization.
+#[cfg(feature = "rayon")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+pub const PARALLEL_DECODE_MIN_LOG_LEN: usize = 4096;
+
+/// Returns the elements in the log that are roots, in the order of the log.
+/// With the `rayon` feature, logs of at least `PARALLEL_DECODE_MIN_LOG_LEN`
+/// elements are split across threads.
+pub(crate) fn filter_roots<T, F>(log: &[T], is_root: F) -> Vec<T>
+where
+    T: Copy + Send + Sync,
+    F: Fn(T) -> bool + Sync,
+{
+    #[cfg(feature = "rayon")]
+    if log.len() >= PARALLEL_DECODE_MIN_LOG_LEN {
+        use rayon::prelude::*;
+        return log.par_iter().copied().filter(|&x| is_root(x)).collect();
+    }
+    log.iter().copied().filter(|&x| is_root(x)).collect()
+}
 
 cfg_power_table! {
     use crate::precompute::INVERSE_TABLE_U16;
@@ -19,6 +56,43 @@ pub struct PowerSumQuackU32 {
     count: u32,
 }
 
+/// The result of decoding a quACK with
+/// [decode_with_log_detailed](trait.PowerSumQuack.html#method.decode_with_log_detailed).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedDiff<T> {
+    /// The decoded elements, in log order. An element appears as many times
+    /// as it appears in the log, which may be more times than it is in the
+    /// quACK.
+    pub elements: Vec<T>,
+    /// The number of elements in the quACK that were not decoded, e.g.,
+    /// because they are not in the log.
+    pub missing_unexplained: u32,
+    /// Whether every element in the quACK was decoded.
+    pub complete: bool,
+}
+
+impl<T> DecodedDiff<T> {
+    fn new(elements: Vec<T>, count: u32) -> Self {
+        let missing_unexplained = count.saturating_sub(elements.len() as u32);
+        Self {
+            elements,
+            missing_unexplained,
+            complete: missing_unexplained == 0,
+        }
+    }
+}
+
+impl<T: Hash + Eq + Clone> DecodedDiff<T> {
+    /// The number of times each decoded element appears.
+    pub fn counts(&self) -> HashMap<T, usize> {
+        let mut counts = HashMap::new();
+        for x in &self.elements {
+            *counts.entry(x.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+}
+
 /// A quACK represented by a threshold number of power sums.
 ///
 /// The power sum quACK is useful for decoding a set difference of elements
@@ -60,6 +134,29 @@ pub trait PowerSumQuack {
     /// Insert an element in the quACK.
     fn insert(&mut self, value: Self::Element);
 
+    /// Insert the elements in the quACK, in order. Equivalent to calling
+    /// [insert](trait.PowerSumQuack.html#method.insert) on each element, but
+    /// implementations may compute the power sums of many elements at once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quack::prelude::*;
+    ///
+    /// let mut quack = PowerSumQuackU32::new(10);
+    /// quack.insert_batch(&[1, 2, 3, 4, 5]);
+    /// assert_eq!(quack.count(), 5);
+    /// assert_eq!(quack.last_value(), Some(5));
+    /// ```
+    fn insert_batch(&mut self, values: &[Self::Element])
+    where
+        Self::Element: Copy,
+    {
+        for &value in values {
+            self.insert(value);
+        }
+    }
+
     /// Remove an element in the quACK. Does not validate that the element
     /// had actually been inserted in the quACK.
     fn remove(&mut self, value: Self::Element);
@@ -73,6 +170,17 @@ pub trait PowerSumQuack {
     /// does not consider the root multiplicity in the polynomial. If the log is
     /// incomplete, there will be fewer roots returned than the actual number of
     /// elements represented by the quACK.
+    ///
+    /// With the `rayon` feature, the 32-bit, 16-bit, and 64-bit power sum
+    /// quACKs evaluate long logs on multiple threads. The roots are still
+    /// returned in the order of the log.
+    ///
+    /// # Panics
+    ///
+    /// May panic if the quACK cannot be decoded, see
+    /// [can_decode](trait.PowerSumQuack.html#method.can_decode). Use
+    /// [try_decode_with_log](trait.PowerSumQuack.html#method.try_decode_with_log)
+    /// to return an error instead.
     fn decode_with_log(&self, log: &[Self::Element]) -> Vec<Self::Element>;
 
     /// Convert the `n` modular power sums that represent the elements in the
@@ -83,8 +191,7 @@ pub trait PowerSumQuack {
     /// # Examples
     ///
     /// ```
-    /// use quack::{PowerSumQuack, PowerSumQuackU32};
-    /// use quack::arithmetic::{ModularInteger, ModularArithmetic};
+    /// use quack::prelude::*;
     ///
     /// const THRESHOLD: usize = 20;
     /// const ROOT1: u32 = 10;
@@ -128,8 +235,7 @@ pub trait PowerSumQuack {
     /// # Examples
     ///
     /// ```
-    /// use quack::{PowerSumQuack, PowerSumQuackU32};
-    /// use quack::arithmetic::{ModularInteger, ModularArithmetic};
+    /// use quack::prelude::*;
     ///
     /// const THRESHOLD: usize = 20;
     ///
@@ -159,6 +265,204 @@ pub trait PowerSumQuack {
     /// Similar to [sub_assign](trait.PowerSumQuack.html#method.sub_assign)
     /// but returns the difference as a new quACK.
     fn sub(self, rhs: Self) -> Self;
+
+    /// Similar to [sub_assign](trait.PowerSumQuack.html#method.sub_assign)
+    /// but returns [Error::ThresholdMismatch] instead of panicking if the two
+    /// quACKs have different thresholds. The quACK is unchanged on error.
+    /// Like [sub_assign](trait.PowerSumQuack.html#method.sub_assign), the
+    /// count wraps if `rhs` has more elements than this quACK.
+    fn try_sub_assign(&mut self, rhs: Self) -> Result<(), Error>
+    where
+        Self: Sized,
+    {
+        if self.threshold() != rhs.threshold() {
+            return Err(Error::ThresholdMismatch {
+                expected: self.threshold(),
+                actual: rhs.threshold(),
+            });
+        }
+        self.sub_assign(rhs);
+        Ok(())
+    }
+
+    /// Similar to [sub](trait.PowerSumQuack.html#method.sub) but returns
+    /// [Error::ThresholdMismatch] instead of panicking if the two quACKs have
+    /// different thresholds.
+    fn try_sub(self, rhs: Self) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let mut result = self;
+        result.try_sub_assign(rhs)?;
+        Ok(result)
+    }
+
+    /// Adds another power sum quACK to this power sum quACK, e.g., to merge
+    /// the quACKs of packets received on different cores.
+    ///
+    /// The sum of a quACK with `x` elements and a quACK with `y` elements is a
+    /// quACK with `x + y` elements, as if the elements in `rhs` were inserted
+    /// in this quACK after its own elements. The last value is the last value
+    /// of `rhs` if it has one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the quACKs have different thresholds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quack::prelude::*;
+    ///
+    /// let mut core1 = quack![1, 2, 3];
+    /// let core2 = quack![4, 5];
+    /// core1.add_assign(&core2);
+    ///
+    /// let mut diff = quack![1, 2, 3, 4, 5, 6];
+    /// diff.sub_assign(core1);
+    /// assert_eq!(diff.decode_with_log(&[1, 2, 3, 4, 5, 6]), vec![6]);
+    /// ```
+    fn add_assign(&mut self, rhs: &Self) {
+        if let Err(e) = self.try_add_assign(rhs) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Similar to [add_assign](trait.PowerSumQuack.html#method.add_assign) but
+    /// returns the sum as a new quACK.
+    fn merge(self, rhs: &Self) -> Self
+    where
+        Self: Sized,
+    {
+        let mut result = self;
+        result.add_assign(rhs);
+        result
+    }
+
+    /// Similar to [add_assign](trait.PowerSumQuack.html#method.add_assign) but
+    /// returns [Error::ThresholdMismatch] instead of panicking if the two
+    /// quACKs have different thresholds. The quACK is unchanged on error.
+    fn try_add_assign(&mut self, rhs: &Self) -> Result<(), Error>;
+
+    /// Similar to [remove](trait.PowerSumQuack.html#method.remove) but returns
+    /// [Error::CountUnderflow] instead of wrapping the count if the quACK is
+    /// empty.
+    fn try_remove(&mut self, value: Self::Element) -> Result<(), Error> {
+        if self.count() == 0 {
+            return Err(Error::CountUnderflow {
+                count: 0,
+                removed: 1,
+            });
+        }
+        self.remove(value);
+        Ok(())
+    }
+
+    /// Similar to [to_coeffs](trait.PowerSumQuack.html#method.to_coeffs) but
+    /// returns [Error::ExceedsThreshold] if the quACK has more elements than
+    /// its threshold.
+    fn try_to_coeffs(&self) -> Result<CoefficientVector<Self::ModularElement>, Error> {
+        self.check_threshold()?;
+        Ok(self.to_coeffs())
+    }
+
+    /// Similar to [decode_with_log](trait.PowerSumQuack.html#method.decode_with_log)
+    /// but returns [Error::ExceedsThreshold] if the quACK has more elements
+    /// than its threshold.
+    fn try_decode_with_log(&self, log: &[Self::Element]) -> Result<Vec<Self::Element>, Error> {
+        self.check_threshold()?;
+        Ok(self.decode_with_log(log))
+    }
+
+    /// Similar to [decode_with_log](trait.PowerSumQuack.html#method.decode_with_log)
+    /// but returns the indices of the roots in the log, in increasing order,
+    /// so callers that keep metadata per log entry do not have to search the
+    /// log for the decoded values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quack::prelude::*;
+    ///
+    /// let log = [(1, "a"), (2, "b"), (3, "c"), (2, "d")];
+    /// let mut diff = quack![1, 2, 3; threshold = 10];
+    /// diff.sub_assign(quack![1, 3; threshold = 10]);
+    /// let ids = log.iter().map(|&(id, _)| id).collect::<Vec<_>>();
+    /// let indices = diff.decode_with_log_indices(&ids);
+    /// assert_eq!(indices, vec![1, 3]);
+    /// assert_eq!(log[indices[0]].1, "b");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics like `decode_with_log` if the quACK cannot be decoded.
+    fn decode_with_log_indices(&self, log: &[Self::Element]) -> Vec<usize>
+    where
+        Self::Element: PartialEq,
+    {
+        // The roots are the elements of the log that evaluate to zero, in log
+        // order, so each root is the next element of the log with its value.
+        let roots = self.decode_with_log(log);
+        let mut roots = roots.iter().peekable();
+        log.iter()
+            .enumerate()
+            .filter(|&(_, x)| roots.next_if(|&root| root == x).is_some())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Similar to [decode_with_log](trait.PowerSumQuack.html#method.decode_with_log)
+    /// but also returns how many elements were not decoded.
+    ///
+    /// Unlike `decode_with_log`, nothing is decoded from an empty quACK, and a
+    /// quACK with more elements than its threshold returns all of its elements
+    /// as unexplained instead of panicking.
+    fn decode_with_log_detailed(&self, log: &[Self::Element]) -> DecodedDiff<Self::Element> {
+        let elements = if self.count() == 0 || self.check_threshold().is_err() {
+            vec![]
+        } else {
+            self.decode_with_log(log)
+        };
+        DecodedDiff::new(elements, self.count())
+    }
+
+    /// Whether the quACK can be decoded, i.e., it has at most the threshold
+    /// number of elements. A quACK cannot be decoded if more than the
+    /// threshold number of elements are missing, or if more elements were
+    /// subtracted than inserted, which wraps the count.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quack::prelude::*;
+    ///
+    /// let mut diff = quack![1, 2, 3, 4; threshold = 2];
+    /// diff.sub_assign(quack![1, 2; threshold = 2]);
+    /// assert!(diff.can_decode());
+    /// diff.insert(5);
+    /// assert!(!diff.can_decode());
+    /// ```
+    fn can_decode(&self) -> bool {
+        self.count() as usize <= self.threshold()
+    }
+
+    /// Returns [Error::ExceedsThreshold] if the quACK has more elements than
+    /// its threshold, i.e., if it cannot be decoded.
+    fn check_threshold(&self) -> Result<(), Error> {
+        if !self.can_decode() {
+            trace_event!(
+                WARN,
+                count = self.count(),
+                threshold = self.threshold(),
+                "quACK exceeds threshold"
+            );
+            return Err(Error::ExceedsThreshold {
+                count: self.count(),
+                threshold: self.threshold(),
+            });
+        }
+        Ok(())
+    }
 }
 
 impl PowerSumQuack for PowerSumQuackU32 {
@@ -185,6 +489,7 @@ impl PowerSumQuack for PowerSumQuackU32 {
         self.last_value.map(|value| value.value())
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(self)))]
     fn insert(&mut self, value: Self::Element) {
         let size = self.power_sums.len();
         let x = ModularInteger::new(value);
@@ -198,6 +503,36 @@ impl PowerSumQuack for PowerSumQuackU32 {
         self.last_value = Some(x);
     }
 
+    /// Computes the powers of blocks of 8 elements at once,
+    /// so the multiplications of different elements are independent, and adds
+    /// the sum of each power in the block with a single modular reduction.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all, fields(len = values.len())))]
+    fn insert_batch(&mut self, values: &[Self::Element]) {
+        let mut blocks = values.chunks_exact(INSERT_BATCH_LANES);
+        for block in &mut blocks {
+            let xs: [ModularInteger<u32>; INSERT_BATCH_LANES] =
+                std::array::from_fn(|i| ModularInteger::new(block[i]));
+            let mut ys = xs;
+            for power_sum in self.power_sums.iter_mut() {
+                let sum = ys.iter().map(|y| y.value() as u64).sum::<u64>();
+                power_sum.add_assign(ModularInteger::new(
+                    (sum % ModularInteger::<u32>::modulus_big()) as u32,
+                ));
+                for (y, &x) in ys.iter_mut().zip(&xs) {
+                    y.mul_assign(x);
+                }
+            }
+        }
+        let blocked = values.len() - blocks.remainder().len();
+        self.count = self.count.wrapping_add(blocked as u32);
+        if let Some(&value) = values[..blocked].last() {
+            self.last_value = Some(ModularInteger::new(value));
+        }
+        for &value in blocks.remainder() {
+            self.insert(value);
+        }
+    }
+
     fn remove(&mut self, value: Self::Element) {
         let size = self.power_sums.len();
         let x = ModularInteger::new(value);
@@ -215,15 +550,28 @@ impl PowerSumQuack for PowerSumQuackU32 {
         }
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(count = self.count, threshold = self.threshold(), log_len = log.len()),
+    ))]
     fn decode_with_log(&self, log: &[Self::Element]) -> Vec<Self::Element> {
         if self.count() == 0 {
             return log.to_vec();
         }
         let coeffs = self.to_coeffs();
-        log.iter()
-            .filter(|&&x| arithmetic::eval(&coeffs, x).value() == 0)
-            .copied()
-            .collect()
+        let decoded = filter_roots(log, |x| arithmetic::eval(&coeffs, x).value() == 0);
+        if decoded.len() < self.count as usize {
+            trace_event!(
+                WARN,
+                count = self.count,
+                decoded = decoded.len(),
+                "decoded fewer elements than the quACK count"
+            );
+        } else {
+            trace_event!(DEBUG, decoded = decoded.len(), "decoded quACK");
+        }
+        decoded
     }
 
     /// Convert the `n` modular power sums that represent the elements in the
@@ -234,8 +582,7 @@ impl PowerSumQuack for PowerSumQuackU32 {
     /// # Examples
     ///
     /// ```
-    /// use quack::{PowerSumQuack, PowerSumQuackU32};
-    /// use quack::arithmetic::{ModularInteger, ModularArithmetic};
+    /// use quack::prelude::*;
     ///
     /// const THRESHOLD: usize = 20;
     /// const ROOT1: u32 = 10;
@@ -260,7 +607,20 @@ impl PowerSumQuack for PowerSumQuackU32 {
     ///     ]);
     /// }
     /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(count = self.count, threshold = self.threshold()),
+    ))]
     fn to_coeffs(&self) -> CoefficientVector<Self::ModularElement> {
+        if !self.can_decode() {
+            trace_event!(
+                ERROR,
+                count = self.count,
+                threshold = self.threshold(),
+                "quACK exceeds threshold"
+            );
+        }
         let mut coeffs = (0..self.count())
             .map(|_| ModularInteger::new(0))
             .collect::<Vec<_>>();
@@ -295,8 +655,7 @@ impl PowerSumQuack for PowerSumQuackU32 {
     /// # Examples
     ///
     /// ```
-    /// use quack::{PowerSumQuack, PowerSumQuackU32};
-    /// use quack::arithmetic::{ModularInteger, ModularArithmetic};
+    /// use quack::prelude::*;
     ///
     /// const THRESHOLD: usize = 20;
     ///
@@ -322,16 +681,35 @@ impl PowerSumQuack for PowerSumQuackU32 {
     /// }
     /// ```
     fn sub_assign(&mut self, rhs: Self) {
-        assert_eq!(
-            self.threshold(),
-            rhs.threshold(),
-            "expected subtracted quacks to have the same threshold"
-        );
+        if let Err(e) = self.try_sub_assign(rhs) {
+            panic!("{}", e);
+        }
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(count = self.count, rhs_count = rhs.count),
+    ))]
+    fn try_sub_assign(&mut self, rhs: Self) -> Result<(), Error> {
+        if self.threshold() != rhs.threshold() {
+            trace_event!(
+                WARN,
+                threshold = self.threshold(),
+                rhs_threshold = rhs.threshold(),
+                "subtracted quACKs have different thresholds"
+            );
+            return Err(Error::ThresholdMismatch {
+                expected: self.threshold(),
+                actual: rhs.threshold(),
+            });
+        }
         for (i, sum) in self.power_sums.iter_mut().enumerate() {
             sum.sub_assign(rhs.power_sums[i]);
         }
         self.count = self.count.wrapping_sub(rhs.count);
         self.last_value = None;
+        Ok(())
     }
 
     fn sub(self, rhs: Self) -> Self {
@@ -339,6 +717,640 @@ impl PowerSumQuack for PowerSumQuackU32 {
         result.sub_assign(rhs);
         result
     }
+
+    fn try_add_assign(&mut self, rhs: &Self) -> Result<(), Error> {
+        if self.threshold() != rhs.threshold() {
+            trace_event!(
+                WARN,
+                threshold = self.threshold(),
+                rhs_threshold = rhs.threshold(),
+                "added quACKs have different thresholds"
+            );
+            return Err(Error::ThresholdMismatch {
+                expected: self.threshold(),
+                actual: rhs.threshold(),
+            });
+        }
+        for (sum, &rhs_sum) in self.power_sums.iter_mut().zip(&rhs.power_sums) {
+            sum.add_assign(rhs_sum);
+        }
+        self.count = self.count.wrapping_add(rhs.count);
+        self.last_value = rhs.last_value.or(self.last_value);
+        Ok(())
+    }
+}
+
+/// Inserts the elements in blocks with
+/// [insert_batch](trait.PowerSumQuack.html#method.insert_batch), without
+/// allocating.
+impl Extend<u32> for PowerSumQuackU32 {
+    fn extend<I: IntoIterator<Item = u32>>(&mut self, iter: I) {
+        let mut block = [0; INSERT_BATCH_LANES];
+        let mut len = 0;
+        for value in iter {
+            block[len] = value;
+            len += 1;
+            if len == INSERT_BATCH_LANES {
+                self.insert_batch(&block);
+                len = 0;
+            }
+        }
+        self.insert_batch(&block[..len]);
+    }
+}
+
+impl<'a> Extend<&'a u32> for PowerSumQuackU32 {
+    fn extend<I: IntoIterator<Item = &'a u32>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+/// Collects the elements in a quACK with the
+/// [crate::builder::DEFAULT_THRESHOLD]. Use
+/// [crate::QuackBuilder::build_from] for a different threshold.
+///
+/// # Examples
+///
+/// ```
+/// use quack::prelude::*;
+///
+/// let log = vec![1, 2, 3, 4, 5];
+/// let sent: PowerSumQuackU32 = log.iter().copied().collect();
+/// let mut received = QuackBuilder::power_sum().build().unwrap();
+/// received.extend(log.iter().filter(|&&x| x != 3));
+/// assert_eq!(sent.sub(received).decode_with_log(&log), vec![3]);
+/// ```
+impl FromIterator<u32> for PowerSumQuackU32 {
+    fn from_iter<I: IntoIterator<Item = u32>>(iter: I) -> Self {
+        let mut quack = Self::new(crate::builder::DEFAULT_THRESHOLD);
+        quack.extend(iter);
+        quack
+    }
+}
+
+impl PowerSumQuackU32 {
+    /// Changes the threshold of the quACK, e.g., to adapt it to the observed
+    /// loss rate.
+    ///
+    /// Shrinking the threshold keeps the first `new_threshold` power sums,
+    /// which are the power sums of the same elements with the smaller
+    /// threshold. Growing the threshold needs the higher powers of the
+    /// elements, so it returns [Error::CannotGrow] unless the quACK is empty.
+    /// Use [PowerSumQuackU32::resize_threshold_with_elements] to grow a quACK
+    /// with elements. The other endpoint must resize its quACK to the same
+    /// threshold before the quACKs can be subtracted.
+    ///
+    /// Also returns [Error::ZeroThreshold] if the new threshold is zero, or
+    /// [Error::ThresholdTooLarge] like [crate::QuackBuilder::build]. The quACK
+    /// is unchanged on error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quack::prelude::*;
+    ///
+    /// let mut quack = quack![1, 2, 3; threshold = 10];
+    /// quack.resize_threshold(5).unwrap();
+    /// assert_eq!(quack.threshold(), 5);
+    /// assert_eq!(
+    ///     quack.resize_threshold(10).unwrap_err(),
+    ///     Error::CannotGrow { count: 3 }
+    /// );
+    /// ```
+    pub fn resize_threshold(&mut self, new_threshold: usize) -> Result<(), Error> {
+        if new_threshold == 0 {
+            return Err(Error::ZeroThreshold);
+        }
+        if new_threshold > self.threshold() {
+            if self.count != 0 || self.power_sums.iter().any(|sum| sum.value() != 0) {
+                return Err(Error::CannotGrow { count: self.count });
+            }
+            precompute::ensure_max_threshold(new_threshold)?;
+        }
+        self.power_sums
+            .resize(new_threshold, ModularInteger::new(0));
+        Ok(())
+    }
+
+    /// Similar to [PowerSumQuackU32::resize_threshold] but grows the threshold
+    /// of a quACK with elements by re-encoding the elements, which must be the
+    /// multiset of elements in the quACK in any order. Returns
+    /// [Error::CannotGrow] if the elements do not encode to this quACK.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quack::prelude::*;
+    ///
+    /// let mut quack = quack![1, 2, 3; threshold = 2];
+    /// quack.resize_threshold_with_elements(10, &[3, 2, 1]).unwrap();
+    /// assert_eq!(quack.threshold(), 10);
+    /// assert!(quack.resize_threshold_with_elements(20, &[1, 2]).is_err());
+    /// ```
+    pub fn resize_threshold_with_elements(
+        &mut self,
+        new_threshold: usize,
+        elements: &[u32],
+    ) -> Result<(), Error> {
+        if new_threshold <= self.threshold() {
+            return self.resize_threshold(new_threshold);
+        }
+        precompute::ensure_max_threshold(new_threshold)?;
+        let mut quack = Self::new(new_threshold);
+        quack.insert_batch(elements);
+        if quack.count != self.count || quack.power_sums[..self.threshold()] != self.power_sums {
+            return Err(Error::CannotGrow { count: self.count });
+        }
+        self.power_sums = quack.power_sums;
+        Ok(())
+    }
+
+    /// The number of bytes in the quACK serialized with `bincode`.
+    pub fn serialized_len(&self) -> usize {
+        8 + 4 * self.power_sums.len() + 1 + 4 * usize::from(self.last_value.is_some()) + 4
+    }
+
+    /// Serializes the quACK with `bincode` into the start of the buffer,
+    /// returning the number of bytes written. Does not allocate.
+    pub fn serialize_into(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        let len = self.serialized_len();
+        if buf.len() < len {
+            return Err(Error::BufferTooSmall { needed: len });
+        }
+        buf[..8].copy_from_slice(&(self.power_sums.len() as u64).to_le_bytes());
+        let mut offset = 8;
+        for sum in &self.power_sums {
+            buf[offset..offset + 4].copy_from_slice(&sum.value().to_le_bytes());
+            offset += 4;
+        }
+        match self.last_value {
+            Some(value) => {
+                buf[offset] = 1;
+                buf[offset + 1..offset + 5].copy_from_slice(&value.value().to_le_bytes());
+                offset += 5;
+            }
+            None => {
+                buf[offset] = 0;
+                offset += 1;
+            }
+        }
+        buf[offset..offset + 4].copy_from_slice(&self.count.to_le_bytes());
+        Ok(len)
+    }
+
+    /// Deserializes a quACK serialized with `bincode` into this quACK,
+    /// reusing its allocation, and returns the number of bytes read. The
+    /// serialized quACK must have the same threshold. Does not allocate
+    /// unless it returns [Error::MalformedBuffer]. The quACK is unchanged on
+    /// error.
+    pub fn deserialize_into(&mut self, bytes: &[u8]) -> Result<usize, Error> {
+        let read_u32 = |offset: usize| -> Result<u32, Error> {
+            let needed = offset + 4;
+            let bytes = bytes
+                .get(offset..needed)
+                .ok_or(Error::BufferTooSmall { needed })?;
+            Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+        };
+        let read_element = |offset: usize| -> Result<ModularInteger<u32>, Error> {
+            let n = read_u32(offset)?;
+            if n >= ModularInteger::<u32>::modulus() {
+                return Err(Error::MalformedBuffer(format!(
+                    "{n} is not less than the modulus"
+                )));
+            }
+            Ok(ModularInteger::new(n))
+        };
+        let threshold = bytes
+            .get(..8)
+            .ok_or(Error::BufferTooSmall { needed: 8 })?
+            .try_into()
+            .map(u64::from_le_bytes)
+            .unwrap();
+        if threshold != self.power_sums.len() as u64 {
+            return Err(Error::ThresholdMismatch {
+                expected: self.power_sums.len(),
+                actual: threshold as usize,
+            });
+        }
+        let mut offset = 8 + 4 * self.power_sums.len();
+        let tag = *bytes
+            .get(offset)
+            .ok_or(Error::BufferTooSmall { needed: offset + 1 })?;
+        let last_value = match tag {
+            0 => None,
+            1 => Some(read_element(offset + 1)?),
+            _ => return Err(Error::MalformedBuffer(format!("invalid option tag {tag}"))),
+        };
+        offset += 1 + 4 * usize::from(tag);
+        let count = read_u32(offset)?;
+        // Check every power sum before changing the quACK.
+        for i in 0..self.power_sums.len() {
+            read_element(8 + 4 * i)?;
+        }
+        for (i, sum) in self.power_sums.iter_mut().enumerate() {
+            *sum = read_element(8 + 4 * i)?;
+        }
+        self.last_value = last_value;
+        self.count = count;
+        Ok(offset + 4)
+    }
+
+    /// Deserializes a quACK serialized with `bincode` from the start of the
+    /// buffer, returning the quACK and the number of bytes read.
+    ///
+    /// Unlike `bincode::deserialize`, this is safe to call on untrusted input.
+    /// It returns an error before allocating if the threshold is zero or
+    /// greater than `max_threshold`, or if the buffer is too short for the
+    /// threshold, so it never allocates more than the length of the buffer.
+    pub fn deserialize_bounded(bytes: &[u8], max_threshold: usize) -> Result<(Self, usize), Error> {
+        let threshold = bytes
+            .get(..8)
+            .ok_or(Error::BufferTooSmall { needed: 8 })?
+            .try_into()
+            .map(u64::from_le_bytes)
+            .unwrap();
+        if threshold == 0 {
+            return Err(Error::ZeroThreshold);
+        }
+        if threshold > max_threshold as u64 {
+            return Err(Error::MalformedBuffer(format!(
+                "threshold {threshold} exceeds the maximum {max_threshold}"
+            )));
+        }
+        let needed = threshold
+            .checked_mul(4)
+            .and_then(|n| n.checked_add(8 + 1 + 4))
+            .map_or(usize::MAX, |n| usize::try_from(n).unwrap_or(usize::MAX));
+        if bytes.len() < needed {
+            return Err(Error::BufferTooSmall { needed });
+        }
+        let mut quack = Self::new(threshold as usize);
+        let len = quack.deserialize_into(bytes)?;
+        Ok((quack, len))
+    }
+
+    /// Deserializes a quACK serialized with `bincode`, returning an error
+    /// instead of panicking if the bytes are truncated, have trailing bytes,
+    /// have a value that is not less than the modulus, or are otherwise
+    /// malformed. The threshold must be at most the one set
+    /// by [crate::global_config_set_max_power_sum_threshold].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quack::prelude::*;
+    ///
+    /// let bytes = bincode::serialize(&quack![1, 2, 3]).unwrap();
+    /// let quack = PowerSumQuackU32::try_deserialize(&bytes).unwrap();
+    /// assert_eq!(quack.count(), 3);
+    /// assert!(PowerSumQuackU32::try_deserialize(&bytes[1..]).is_err());
+    /// ```
+    pub fn try_deserialize(bytes: &[u8]) -> Result<Self, Error> {
+        let (quack, len) = Self::deserialize_bounded(bytes, precompute::max_threshold())?;
+        if len != bytes.len() {
+            return Err(Error::MalformedBuffer(format!(
+                "{} trailing bytes",
+                bytes.len() - len
+            )));
+        }
+        Ok(quack)
+    }
+
+    /// Serializes the quACK like [PowerSumQuackU32::serialize_into] followed
+    /// by a CRC-32 of the serialized bytes, returning the number of bytes
+    /// written. Does not allocate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quack::prelude::*;
+    ///
+    /// let quack = quack![1, 2, 3];
+    /// let mut buf = [0; 128];
+    /// let len = quack.serialize_with_checksum(&mut buf).unwrap();
+    /// assert_eq!(len, quack.serialized_len() + 4);
+    ///
+    /// buf[20] ^= 1;
+    /// let result = PowerSumQuackU32::deserialize_with_checksum(&buf[..len], 20);
+    /// assert!(matches!(result, Err(Error::ChecksumMismatch { .. })));
+    /// ```
+    pub fn serialize_with_checksum(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        let needed = self.serialized_len() + checksum::LEN;
+        if buf.len() < needed {
+            return Err(Error::BufferTooSmall { needed });
+        }
+        let len = self.serialize_into(buf)?;
+        Ok(checksum::write(buf, len))
+    }
+
+    /// Deserializes a quACK serialized with
+    /// [PowerSumQuackU32::serialize_with_checksum] from the start of the
+    /// buffer like [PowerSumQuackU32::deserialize_bounded], returning the
+    /// quACK and the number of bytes read. Returns
+    /// [Error::ChecksumMismatch] if the bytes were corrupted.
+    pub fn deserialize_with_checksum(
+        bytes: &[u8],
+        max_threshold: usize,
+    ) -> Result<(Self, usize), Error> {
+        // The checksum follows the quACK, so a corrupted threshold is
+        // detected as a malformed or truncated quACK instead.
+        let (quack, len) = Self::deserialize_bounded(bytes, max_threshold)?;
+        let len = checksum::verify(bytes, len)?;
+        Ok((quack, len))
+    }
+
+    /// The number of bytes in the quACK serialized with
+    /// [PowerSumQuackU32::serialize_compact].
+    pub fn serialized_compact_len(&self) -> usize {
+        let sums: usize = self
+            .power_sums
+            .iter()
+            .map(|sum| varint::encoded_len(sum.value().into()))
+            .sum();
+        let last_value = self
+            .last_value
+            .map_or(0, |value| varint::encoded_len(value.value().into()));
+        varint::encoded_len(self.power_sums.len() as u64)
+            + sums
+            + 1
+            + last_value
+            + varint::encoded_len(self.count.into())
+    }
+
+    /// Serializes the quACK into the start of the buffer like
+    /// [PowerSumQuackU32::serialize_into], but with each integer
+    /// [LEB128](https://en.wikipedia.org/wiki/LEB128)-encoded, returning the
+    /// number of bytes written. Does not allocate.
+    ///
+    /// The encoding is smaller than `bincode` when the power sums are small,
+    /// e.g., when the quACK is empty. The power sums of even one element are
+    /// spread over the field, and most take 5 bytes instead of 4, so compare
+    /// [PowerSumQuackU32::serialized_compact_len] with
+    /// [PowerSumQuackU32::serialized_len] to pick an encoding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quack::prelude::*;
+    ///
+    /// let quack = PowerSumQuackU32::new(20);
+    /// let mut buf = [0; 128];
+    /// let len = quack.serialize_compact(&mut buf).unwrap();
+    /// assert_eq!(len, 23);
+    /// assert_eq!(quack.serialized_len(), 93);
+    /// let (decoded, _) = PowerSumQuackU32::deserialize_compact(&buf[..len], 20).unwrap();
+    /// assert_eq!(decoded.threshold(), 20);
+    /// ```
+    pub fn serialize_compact(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        let len = self.serialized_compact_len();
+        if buf.len() < len {
+            return Err(Error::BufferTooSmall { needed: len });
+        }
+        let mut offset = varint::write(buf, 0, self.power_sums.len() as u64);
+        for sum in &self.power_sums {
+            offset = varint::write(buf, offset, sum.value().into());
+        }
+        match self.last_value {
+            Some(value) => {
+                buf[offset] = 1;
+                offset = varint::write(buf, offset + 1, value.value().into());
+            }
+            None => {
+                buf[offset] = 0;
+                offset += 1;
+            }
+        }
+        offset = varint::write(buf, offset, self.count.into());
+        Ok(offset)
+    }
+
+    /// Deserializes a quACK serialized with
+    /// [PowerSumQuackU32::serialize_compact] from the start of the buffer,
+    /// returning the quACK and the number of bytes read.
+    ///
+    /// Like [PowerSumQuackU32::deserialize_bounded], this is safe to call on
+    /// untrusted input. It returns an error before allocating if the
+    /// threshold is zero or greater than `max_threshold`, or if the buffer is
+    /// too short for the threshold. Power sums outside the field and integers
+    /// that do not fit are malformed.
+    pub fn deserialize_compact(bytes: &[u8], max_threshold: usize) -> Result<(Self, usize), Error> {
+        let read_u32 = |offset: usize| -> Result<(u32, usize), Error> {
+            let (n, end) = varint::read(bytes, offset)?;
+            let n = u32::try_from(n).map_err(|_| {
+                Error::MalformedBuffer(format!("varint at offset {offset} overflows 32 bits"))
+            })?;
+            Ok((n, end))
+        };
+        let read_element = |offset: usize| -> Result<(ModularInteger<u32>, usize), Error> {
+            let (n, end) = read_u32(offset)?;
+            if n >= ModularInteger::<u32>::modulus() {
+                return Err(Error::MalformedBuffer(format!(
+                    "{n} is not less than the modulus"
+                )));
+            }
+            Ok((ModularInteger::new(n), end))
+        };
+
+        let (threshold, mut offset) = varint::read(bytes, 0)?;
+        if threshold == 0 {
+            return Err(Error::ZeroThreshold);
+        }
+        if threshold > max_threshold as u64 {
+            return Err(Error::MalformedBuffer(format!(
+                "threshold {threshold} exceeds the maximum {max_threshold}"
+            )));
+        }
+        // each power sum, the option tag, and the count take at least a byte
+        let needed = threshold.saturating_add(offset as u64 + 2);
+        let needed = usize::try_from(needed).unwrap_or(usize::MAX);
+        if bytes.len() < needed {
+            return Err(Error::BufferTooSmall { needed });
+        }
+        let mut quack = Self::new(threshold as usize);
+        for i in 0..quack.power_sums.len() {
+            (quack.power_sums[i], offset) = read_element(offset)?;
+        }
+        let tag = *bytes
+            .get(offset)
+            .ok_or(Error::BufferTooSmall { needed: offset + 1 })?;
+        offset += 1;
+        quack.last_value = match tag {
+            0 => None,
+            1 => {
+                let (value, end) = read_element(offset)?;
+                offset = end;
+                Some(value)
+            }
+            _ => return Err(Error::MalformedBuffer(format!("invalid option tag {tag}"))),
+        };
+        (quack.count, offset) = read_u32(offset)?;
+        Ok((quack, offset))
+    }
+
+    /// The number of bytes in the delta from `prev` serialized with
+    /// [PowerSumQuackU32::serialize_delta]. The quACKs must have the same
+    /// threshold.
+    pub fn serialized_delta_len(&self, prev: &Self) -> usize {
+        let changed = self
+            .power_sums
+            .iter()
+            .zip(&prev.power_sums)
+            .filter(|(sum, prev)| sum != prev)
+            .count();
+        let last_value = if self.last_value != prev.last_value && self.last_value.is_some() {
+            4
+        } else {
+            0
+        };
+        varint::encoded_len(self.power_sums.len() as u64)
+            + self.power_sums.len().div_ceil(8)
+            + 4 * changed
+            + varint::encoded_len(self.count.wrapping_sub(prev.count).into())
+            + 1
+            + last_value
+    }
+
+    /// Serializes the changes from `prev` to this quACK into the start of the
+    /// buffer, returning the number of bytes written. Applying the delta to
+    /// `prev` with [PowerSumQuackU32::apply_delta] results in this quACK.
+    /// Returns [Error::ThresholdMismatch] if the quACKs have different
+    /// thresholds. Does not allocate.
+    ///
+    /// The delta is a bitmap of the power sums that changed followed by their
+    /// values, the change in the count, and the last value if it changed.
+    /// Every inserted or removed element changes every power sum, so the
+    /// delta is much smaller than the quACK only if few power sums changed,
+    /// e.g., if the receiver sends quACKs periodically and no elements
+    /// arrived since the last one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quack::prelude::*;
+    ///
+    /// let prev = quack![1, 2, 3];
+    /// let mut buf = [0; 128];
+    /// let len = prev.serialize_delta(&prev, &mut buf).unwrap();
+    /// assert_eq!(len, 6);
+    ///
+    /// let next = quack![1, 2, 3, 4];
+    /// let len = next.serialize_delta(&prev, &mut buf).unwrap();
+    /// let mut quack = prev.clone();
+    /// assert_eq!(quack.apply_delta(&buf[..len]), Ok(len));
+    /// assert_eq!(quack.count(), 4);
+    /// assert_eq!(quack.last_value(), Some(4));
+    /// ```
+    pub fn serialize_delta(&self, prev: &Self, buf: &mut [u8]) -> Result<usize, Error> {
+        if self.threshold() != prev.threshold() {
+            return Err(Error::ThresholdMismatch {
+                expected: self.threshold(),
+                actual: prev.threshold(),
+            });
+        }
+        let len = self.serialized_delta_len(prev);
+        if buf.len() < len {
+            return Err(Error::BufferTooSmall { needed: len });
+        }
+        let bitmap = varint::write(buf, 0, self.power_sums.len() as u64);
+        let mut offset = bitmap + self.power_sums.len().div_ceil(8);
+        buf[bitmap..offset].fill(0);
+        for (i, (sum, prev)) in self.power_sums.iter().zip(&prev.power_sums).enumerate() {
+            if sum != prev {
+                buf[bitmap + i / 8] |= 1 << (i % 8);
+                buf[offset..offset + 4].copy_from_slice(&sum.value().to_le_bytes());
+                offset += 4;
+            }
+        }
+        offset = varint::write(buf, offset, self.count.wrapping_sub(prev.count).into());
+        match self.last_value {
+            _ if self.last_value == prev.last_value => {
+                buf[offset] = DELTA_LAST_VALUE_UNCHANGED;
+                offset += 1;
+            }
+            Some(value) => {
+                buf[offset] = DELTA_LAST_VALUE_SOME;
+                buf[offset + 1..offset + 5].copy_from_slice(&value.value().to_le_bytes());
+                offset += 5;
+            }
+            None => {
+                buf[offset] = DELTA_LAST_VALUE_NONE;
+                offset += 1;
+            }
+        }
+        Ok(offset)
+    }
+
+    /// Applies a delta serialized with [PowerSumQuackU32::serialize_delta] to
+    /// this quACK, which must be the `prev` quACK of the delta, and returns
+    /// the number of bytes read. Returns [Error::ThresholdMismatch] if the
+    /// delta is for a different threshold. The quACK is unchanged on error,
+    /// and this does not allocate unless it returns [Error::MalformedBuffer].
+    pub fn apply_delta(&mut self, bytes: &[u8]) -> Result<usize, Error> {
+        let read_u32 = |offset: usize| -> Result<u32, Error> {
+            let needed = offset + 4;
+            let bytes = bytes
+                .get(offset..needed)
+                .ok_or(Error::BufferTooSmall { needed })?;
+            Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+        };
+        let read_element = |offset: usize| -> Result<ModularInteger<u32>, Error> {
+            let n = read_u32(offset)?;
+            if n >= ModularInteger::<u32>::modulus() {
+                return Err(Error::MalformedBuffer(format!(
+                    "{n} is not less than the modulus"
+                )));
+            }
+            Ok(ModularInteger::new(n))
+        };
+
+        // Check the whole delta before changing the quACK.
+        let (threshold, bitmap) = varint::read(bytes, 0)?;
+        if threshold != self.power_sums.len() as u64 {
+            return Err(Error::ThresholdMismatch {
+                expected: self.power_sums.len(),
+                actual: usize::try_from(threshold).unwrap_or(usize::MAX),
+            });
+        }
+        let sums = bitmap + self.power_sums.len().div_ceil(8);
+        let bits = bytes
+            .get(bitmap..sums)
+            .ok_or(Error::BufferTooSmall { needed: sums })?;
+        let changed = |i: usize| bits[i / 8] & (1 << (i % 8)) != 0;
+        if (self.power_sums.len()..8 * bits.len()).any(changed) {
+            return Err(Error::MalformedBuffer(
+                "bitmap has bits past the threshold".to_string(),
+            ));
+        }
+        let num_changed = bits.iter().map(|b| b.count_ones() as usize).sum::<usize>();
+        let mut offset = sums + 4 * num_changed;
+        for i in 0..num_changed {
+            read_element(sums + 4 * i)?;
+        }
+        let (count, end) = varint::read(bytes, offset)?;
+        let count = u32::try_from(count).map_err(|_| {
+            Error::MalformedBuffer(format!("varint at offset {offset} overflows 32 bits"))
+        })?;
+        offset = end;
+        let tag = *bytes
+            .get(offset)
+            .ok_or(Error::BufferTooSmall { needed: offset + 1 })?;
+        let last_value = match tag {
+            DELTA_LAST_VALUE_UNCHANGED => self.last_value,
+            DELTA_LAST_VALUE_SOME => Some(read_element(offset + 1)?),
+            DELTA_LAST_VALUE_NONE => None,
+            _ => return Err(Error::MalformedBuffer(format!("invalid delta tag {tag}"))),
+        };
+        offset += 1 + 4 * usize::from(tag == DELTA_LAST_VALUE_SOME);
+
+        let mut sum = sums;
+        for i in (0..self.power_sums.len()).filter(|&i| changed(i)) {
+            self.power_sums[i] = read_element(sum)?;
+            sum += 4;
+        }
+        self.count = self.count.wrapping_add(count);
+        self.last_value = last_value;
+        Ok(offset)
+    }
 }
 
 cfg_libpari! {
@@ -429,10 +1441,7 @@ cfg_montgomery! {
                 return log.to_vec();
             }
             let coeffs = self.to_coeffs();
-            log.iter()
-                .filter(|&&x| arithmetic::eval(&coeffs, x).value() == 0)
-                .copied()
-                .collect()
+            filter_roots(log, |x| arithmetic::eval(&coeffs, x).value() == 0)
         }
 
         fn to_coeffs(&self) -> CoefficientVector<Self::ModularElement> {
@@ -458,16 +1467,24 @@ cfg_montgomery! {
         }
 
         fn sub_assign(&mut self, rhs: Self) {
-            assert_eq!(
-                self.threshold(),
-                rhs.threshold(),
-                "expected subtracted quacks to have the same threshold"
-            );
-            for (i, sum) in self.power_sums.iter_mut().enumerate() {
-                sum.sub_assign(rhs.power_sums[i]);
+            if let Err(e) = self.try_sub_assign(rhs) {
+                panic!("{}", e);
             }
-            self.count = self.count.wrapping_sub(rhs.count);
+        }
+
+        fn try_sub_assign(&mut self, rhs: Self) -> Result<(), Error> {
+            if self.threshold() != rhs.threshold() {
+                return Err(Error::ThresholdMismatch {
+                    expected: self.threshold(),
+                    actual: rhs.threshold(),
+                });
+            }
+            for (i, sum) in self.power_sums.iter_mut().enumerate() {
+                sum.sub_assign(rhs.power_sums[i]);
+            }
+            self.count = self.count.wrapping_sub(rhs.count);
             self.last_value = None;
+            Ok(())
         }
 
         fn sub(self, rhs: Self) -> Self {
@@ -475,6 +1492,21 @@ cfg_montgomery! {
             result.sub_assign(rhs);
             result
         }
+
+        fn try_add_assign(&mut self, rhs: &Self) -> Result<(), Error> {
+            if self.threshold() != rhs.threshold() {
+                return Err(Error::ThresholdMismatch {
+                    expected: self.threshold(),
+                    actual: rhs.threshold(),
+                });
+            }
+            for (sum, &rhs_sum) in self.power_sums.iter_mut().zip(&rhs.power_sums) {
+                sum.add_assign(rhs_sum);
+            }
+            self.count = self.count.wrapping_add(rhs.count);
+            self.last_value = rhs.last_value.or(self.last_value);
+            Ok(())
+        }
     }
 }
 
@@ -547,10 +1579,7 @@ cfg_power_table! {
             }
             assert!((self.count() as usize) <= self.threshold(), "number of elements must not exceed threshold");
             let coeffs = self.to_coeffs();
-            log.iter()
-                .filter(|&&x| arithmetic::eval(&coeffs, x).value() == 0)
-                .copied()
-                .collect()
+            filter_roots(log, |x| arithmetic::eval(&coeffs, x).value() == 0)
         }
 
         fn to_coeffs(&self) -> CoefficientVector<Self::ModularElement> {
@@ -577,16 +1606,24 @@ cfg_power_table! {
         }
 
         fn sub_assign(&mut self, rhs: Self) {
-            assert_eq!(
-                self.threshold(),
-                rhs.threshold(),
-                "expected subtracted quacks to have the same threshold"
-            );
+            if let Err(e) = self.try_sub_assign(rhs) {
+                panic!("{}", e);
+            }
+        }
+
+        fn try_sub_assign(&mut self, rhs: Self) -> Result<(), Error> {
+            if self.threshold() != rhs.threshold() {
+                return Err(Error::ThresholdMismatch {
+                    expected: self.threshold(),
+                    actual: rhs.threshold(),
+                });
+            }
             for (i, sum) in self.power_sums.iter_mut().enumerate() {
                 sum.sub_assign(rhs.power_sums[i]);
             }
             self.count = self.count.wrapping_sub(rhs.count);
             self.last_value = None;
+            Ok(())
         }
 
         fn sub(self, rhs: Self) -> Self {
@@ -594,12 +1631,28 @@ cfg_power_table! {
             result.sub_assign(rhs);
             result
         }
+
+        fn try_add_assign(&mut self, rhs: &Self) -> Result<(), Error> {
+            if self.threshold() != rhs.threshold() {
+                return Err(Error::ThresholdMismatch {
+                    expected: self.threshold(),
+                    actual: rhs.threshold(),
+                });
+            }
+            for (sum, &rhs_sum) in self.power_sums.iter_mut().zip(&rhs.power_sums) {
+                sum.add_assign(rhs_sum);
+            }
+            self.count = self.count.wrapping_add(rhs.count);
+            self.last_value = rhs.last_value.or(self.last_value);
+            Ok(())
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::quack;
 
     const THRESHOLD: usize = 3;
 
@@ -615,203 +1668,1092 @@ mod test {
     fn test_quack_insert_and_remove_u32() {
         let mut quack = PowerSumQuackU32::new(THRESHOLD);
         quack.insert(10);
-        assert_eq!(quack.count(), 1);
-        assert_eq!(quack.last_value(), Some(10));
+        assert_eq!(quack.count(), 1);
+        assert_eq!(quack.last_value(), Some(10));
+        quack.insert(20);
+        quack.insert(30);
+        assert_eq!(quack.count(), 3);
+        assert_eq!(quack.last_value(), Some(30));
+        quack.remove(10);
+        assert_eq!(quack.count(), 2);
+        assert_eq!(quack.last_value(), Some(30));
+        quack.remove(30);
+        assert_eq!(quack.count(), 1);
+        assert_eq!(quack.last_value(), None);
+    }
+
+    #[test]
+    fn test_quack_to_coeffs_empty_u32() {
+        let quack = PowerSumQuackU32::new(THRESHOLD);
+        assert_eq!(
+            quack.to_coeffs(),
+            CoefficientVector::<ModularInteger<u32>>::new()
+        );
+        let mut coeffs = vec![];
+        quack.to_coeffs_preallocated(&mut coeffs);
+        assert_eq!(coeffs, CoefficientVector::<ModularInteger<u32>>::new());
+    }
+
+    #[test]
+    fn test_quack_to_coeffs_small_u32() {
+        const R1: u32 = 1;
+        const R2: u32 = 2;
+
+        let mut quack = PowerSumQuackU32::new(THRESHOLD);
+        quack.insert(R1);
+        quack.insert(R2);
+        let expected = vec![
+            ModularInteger::<u32>::new(R1 + R2).neg().value(),
+            ModularInteger::<u32>::new(R1 * R2).value(),
+        ]; // x^2 - 3x + 2
+
+        assert_eq!(quack.to_coeffs(), expected);
+        let mut coeffs = (0..quack.count()).map(|_| ModularInteger::new(0)).collect();
+        quack.to_coeffs_preallocated(&mut coeffs);
+        assert_eq!(coeffs, expected);
+    }
+
+    #[test]
+    fn test_quack_to_coeffs_big_u32() {
+        const R1: u64 = 3616712547;
+        const R2: u64 = 2333013068;
+        const R3: u64 = 2234311686;
+        let modulus = ModularInteger::<u32>::modulus_big();
+
+        let mut quack = PowerSumQuackU32::new(THRESHOLD);
+        quack.insert(R1 as u32);
+        quack.insert(R2 as u32);
+        quack.insert(R3 as u32);
+        let expected = vec![
+            ModularInteger::<u32>::new(((R1 + R2 + R3) % modulus) as u32)
+                .neg()
+                .value(),
+            ModularInteger::<u32>::new(((R1 * R2 % modulus + R2 * R3 + R1 * R3) % modulus) as u32)
+                .value(),
+            ModularInteger::<u32>::new(((((R1 * R2) % modulus) * R3) % modulus) as u32)
+                .neg()
+                .value(),
+        ];
+
+        assert_eq!(quack.to_coeffs(), expected);
+        let mut coeffs = (0..quack.count()).map(|_| ModularInteger::new(0)).collect();
+        quack.to_coeffs_preallocated(&mut coeffs);
+        assert_eq!(coeffs, expected);
+    }
+
+    #[test]
+    fn test_decode_empty_u32() {
+        let quack = PowerSumQuackU32::new(THRESHOLD);
+        assert_eq!(quack.decode_with_log(&[]), vec![]);
+        assert_eq!(quack.decode_with_log(&[1]), vec![1]);
+    }
+
+    #[test]
+    fn test_insert_and_decode_u32() {
+        const R1: u32 = 3616712547;
+        const R2: u32 = 2333013068;
+        const R3: u32 = 2234311686;
+        const R4: u32 = 448751902;
+        const R5: u32 = 918748965;
+
+        let mut quack = PowerSumQuackU32::new(THRESHOLD);
+        quack.insert(R1);
+        quack.insert(R2);
+        quack.insert(R3);
+
+        // different orderings
+        // NOTE: the spec of `decode_with_log` doesn't guarantee an order but
+        // here we assume the elements appear in the same order as the list.
+        assert_eq!(quack.decode_with_log(&[R1, R2, R3]), vec![R1, R2, R3]);
+        assert_eq!(quack.decode_with_log(&[R3, R1, R2]), vec![R3, R1, R2]);
+
+        // one extra element in log
+        assert_eq!(quack.decode_with_log(&[R1, R2, R3, R4]), vec![R1, R2, R3]);
+        assert_eq!(quack.decode_with_log(&[R1, R4, R2, R3]), vec![R1, R2, R3]);
+        assert_eq!(quack.decode_with_log(&[R4, R1, R2, R3]), vec![R1, R2, R3]);
+
+        // two extra elements in log
+        assert_eq!(
+            quack.decode_with_log(&[R1, R5, R2, R3, R4]),
+            vec![R1, R2, R3]
+        );
+
+        // not all roots are in log
+        assert_eq!(quack.decode_with_log(&[R1, R2]), vec![R1, R2]);
+        assert_eq!(quack.decode_with_log(&[]), vec![]);
+        assert_eq!(quack.decode_with_log(&[R1, R2, R4]), vec![R1, R2]);
+    }
+
+    #[test]
+    fn test_remove_and_decode_u32() {
+        const R1: u32 = 3616712547;
+        const R2: u32 = 2333013068;
+        const R3: u32 = 2234311686;
+        const R4: u32 = 448751902;
+        const R5: u32 = 918748965;
+
+        let mut quack = PowerSumQuackU32::new(THRESHOLD);
+        quack.insert(R5);
+        quack.insert(R4);
+        quack.insert(R3);
+        quack.insert(R2);
+        quack.insert(R1);
+        quack.remove(R5);
+        quack.remove(R4);
+
+        // R4 and R5 are removed, and R1,2,3 are able to be decoded.
+        // NOTE: the spec of `decode_with_log` doesn't guarantee an order but
+        // here we assume the elements appear in the same order as the list.
+        assert_eq!(quack.decode_with_log(&[R1, R2, R3]), vec![R1, R2, R3]);
+        assert_eq!(
+            quack.decode_with_log(&[R1, R5, R2, R3, R4]),
+            vec![R1, R2, R3]
+        );
+    }
+
+    #[test]
+    fn test_decode_with_multiplicity_u32() {
+        const R1: u32 = 10;
+        const R2: u32 = 20;
+
+        let mut quack = PowerSumQuackU32::new(THRESHOLD);
+        quack.insert(R1);
+        quack.insert(R1);
+
+        assert_eq!(quack.decode_with_log(&[R1, R1]), vec![R1, R1]);
+        assert_eq!(quack.decode_with_log(&[R1]), vec![R1]);
+        assert_eq!(quack.decode_with_log(&[R1, R1, R1]), vec![R1, R1, R1]); // even though one R1 is not in the quACK
+        assert_eq!(quack.decode_with_log(&[R1, R1, R2]), vec![R1, R1]);
+        assert_eq!(quack.decode_with_log(&[R2, R1, R2]), vec![R1]);
+    }
+
+    #[test]
+    fn test_subtract_quacks_with_zero_difference_u32() {
+        let mut q1 = PowerSumQuackU32::new(THRESHOLD);
+        q1.insert(1);
+        q1.insert(2);
+        q1.insert(3);
+        q1.insert(4);
+        q1.insert(5);
+
+        let quack = q1.clone().sub(q1);
+        assert_eq!(quack.threshold(), THRESHOLD);
+        assert_eq!(quack.count(), 0);
+        assert_eq!(quack.last_value(), None);
+        assert_eq!(
+            quack.to_coeffs(),
+            CoefficientVector::<ModularInteger<u32>>::new()
+        );
+    }
+
+    #[test]
+    fn test_subtract_quacks_with_nonzero_difference_u32() {
+        let mut q1 = PowerSumQuackU32::new(THRESHOLD);
+        q1.insert(1);
+        q1.insert(2);
+        q1.insert(3);
+        q1.insert(4);
+        q1.insert(5);
+
+        let mut q2 = PowerSumQuackU32::new(THRESHOLD);
+        q2.insert(1);
+        q2.insert(2);
+
+        let quack = q1.sub(q2);
+        assert_eq!(quack.threshold(), THRESHOLD);
+        assert_eq!(quack.count(), 3);
+        assert_eq!(quack.last_value(), None);
+        assert_eq!(quack.to_coeffs().len(), 3);
+        assert_eq!(quack.decode_with_log(&[1, 2, 3, 4, 5]), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn test_try_sub_assign_threshold_mismatch_u32() {
+        let mut quack = PowerSumQuackU32::new(THRESHOLD);
+        quack.insert(1);
+        let other = PowerSumQuackU32::new(THRESHOLD + 1);
+        assert_eq!(
+            quack.try_sub_assign(other.clone()).unwrap_err(),
+            Error::ThresholdMismatch {
+                expected: THRESHOLD,
+                actual: THRESHOLD + 1
+            }
+        );
+        assert_eq!(quack.count(), 1, "unchanged on error");
+        assert_eq!(quack.last_value(), Some(1));
+        assert!(quack.try_sub(other).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "expected threshold 3, got 4")]
+    fn test_sub_assign_threshold_mismatch_panics_u32() {
+        let mut quack = PowerSumQuackU32::new(THRESHOLD);
+        quack.sub_assign(PowerSumQuackU32::new(THRESHOLD + 1));
+    }
+
+    /// A quACK that only implements the required methods, to test the
+    /// default implementations.
+    struct Minimal(PowerSumQuackU32);
+
+    impl PowerSumQuack for Minimal {
+        type Element = u32;
+        type ModularElement = ModularInteger<u32>;
+
+        fn new(threshold: usize) -> Self {
+            Self(PowerSumQuackU32::new(threshold))
+        }
+
+        fn threshold(&self) -> usize {
+            self.0.threshold()
+        }
+
+        fn count(&self) -> u32 {
+            self.0.count()
+        }
+
+        fn last_value(&self) -> Option<u32> {
+            self.0.last_value()
+        }
+
+        fn insert(&mut self, value: u32) {
+            self.0.insert(value)
+        }
+
+        fn remove(&mut self, value: u32) {
+            self.0.remove(value)
+        }
+
+        fn decode_with_log(&self, log: &[u32]) -> Vec<u32> {
+            self.0.decode_with_log(log)
+        }
+
+        fn to_coeffs(&self) -> CoefficientVector<ModularInteger<u32>> {
+            self.0.to_coeffs()
+        }
+
+        fn to_coeffs_preallocated(&self, coeffs: &mut CoefficientVector<ModularInteger<u32>>) {
+            self.0.to_coeffs_preallocated(coeffs)
+        }
+
+        fn sub_assign(&mut self, rhs: Self) {
+            self.0.sub_assign(rhs.0)
+        }
+
+        fn sub(self, rhs: Self) -> Self {
+            Self(self.0.sub(rhs.0))
+        }
+
+        fn try_add_assign(&mut self, rhs: &Self) -> Result<(), Error> {
+            self.0.try_add_assign(&rhs.0)
+        }
+    }
+
+    /// Subtracts a quACK with more elements, which wraps the count instead
+    /// of returning an error, and one with a different threshold.
+    fn check_try_sub_assign<Q>()
+    where
+        Q: PowerSumQuack,
+        Q::Element: From<u8>,
+    {
+        let mut quack = Q::new(THRESHOLD);
+        quack.insert(1.into());
+        let mut other = Q::new(THRESHOLD + 1);
+        other.insert(1.into());
+        assert_eq!(
+            quack.try_sub_assign(other).unwrap_err(),
+            Error::ThresholdMismatch {
+                expected: THRESHOLD,
+                actual: THRESHOLD + 1
+            }
+        );
+        assert_eq!(quack.count(), 1, "unchanged on error");
+
+        let mut other = Q::new(THRESHOLD);
+        other.insert(1.into());
+        other.insert(2.into());
+        quack.try_sub_assign(other).unwrap();
+        assert_eq!(quack.count(), u32::MAX);
+        assert!(!quack.can_decode());
+        quack.insert(2.into());
+        assert_eq!(quack.count(), 0);
+    }
+
+    #[test]
+    fn test_try_sub_assign_wraps_count() {
+        check_try_sub_assign::<Minimal>();
+        check_try_sub_assign::<PowerSumQuackU32>();
+    }
+
+    #[test]
+    #[cfg(feature = "montgomery")]
+    fn test_try_sub_assign_wraps_count_u64() {
+        check_try_sub_assign::<PowerSumQuackU64>();
+        check_try_sub_assign::<crate::MontgomeryQuack>();
+    }
+
+    #[test]
+    #[cfg(feature = "power_table")]
+    fn test_try_sub_assign_wraps_count_u16() {
+        check_try_sub_assign::<PowerSumQuackU16>();
+        check_try_sub_assign::<crate::PowerTableQuack>();
+    }
+
+    #[test]
+    fn test_default_add_assign() {
+        let mut quack = Minimal::new(THRESHOLD);
+        quack.insert(1);
+        let mut other = Minimal::new(THRESHOLD);
+        other.insert(2);
+        quack.add_assign(&other);
+        assert_eq!(quack.count(), 2);
+        assert_eq!(quack.last_value(), Some(2));
+        let quack = quack.merge(&other);
+        assert_eq!(quack.count(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected threshold 3, got 4")]
+    fn test_default_add_assign_threshold_mismatch_panics() {
+        let mut quack = Minimal::new(THRESHOLD);
+        quack.add_assign(&Minimal::new(THRESHOLD + 1));
+    }
+
+    #[test]
+    fn test_add_assign_u32() {
+        let mut quack = quack![1, 2; threshold = THRESHOLD];
+        quack.add_assign(&quack![3, 4; threshold = THRESHOLD]);
+        assert_eq!(
+            bincode::serialize(&quack).unwrap(),
+            bincode::serialize(&quack![1, 2, 3, 4; threshold = THRESHOLD]).unwrap()
+        );
+
+        // an empty quACK keeps the last value
+        quack.add_assign(&PowerSumQuackU32::new(THRESHOLD));
+        assert_eq!(quack.last_value(), Some(4));
+        assert_eq!(quack.count(), 4);
+
+        let mut diff = quack![1, 2, 3, 4, 5; threshold = THRESHOLD];
+        diff.sub_assign(quack);
+        assert_eq!(diff.decode_with_log(&[1, 2, 3, 4, 5]), vec![5]);
+    }
+
+    #[test]
+    fn test_try_add_assign_threshold_mismatch_u32() {
+        let mut quack = quack![1; threshold = THRESHOLD];
+        let other = quack![2; threshold = THRESHOLD + 1];
+        assert_eq!(
+            quack.try_add_assign(&other).unwrap_err(),
+            Error::ThresholdMismatch {
+                expected: THRESHOLD,
+                actual: THRESHOLD + 1
+            }
+        );
+        assert_eq!(quack.count(), 1, "unchanged on error");
+        assert_eq!(quack.last_value(), Some(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "expected threshold 3, got 4")]
+    fn test_add_assign_threshold_mismatch_panics_u32() {
+        let mut quack = PowerSumQuackU32::new(THRESHOLD);
+        quack.add_assign(&PowerSumQuackU32::new(THRESHOLD + 1));
+    }
+
+    #[test]
+    fn test_resize_threshold_shrink_u32() {
+        let mut quack = quack![1, 2, 3; threshold = THRESHOLD];
+        quack.resize_threshold(THRESHOLD - 1).unwrap();
+        assert_eq!(
+            bincode::serialize(&quack).unwrap(),
+            bincode::serialize(&quack![1, 2, 3; threshold = THRESHOLD - 1]).unwrap()
+        );
+        assert_eq!(quack.resize_threshold(0).unwrap_err(), Error::ZeroThreshold);
+        assert_eq!(quack.threshold(), THRESHOLD - 1, "unchanged on error");
+
+        let mut diff = quack![1, 2, 3, 4, 5; threshold = THRESHOLD - 1];
+        diff.sub_assign(quack);
+        assert_eq!(diff.decode_with_log(&[1, 2, 3, 4, 5]), vec![4, 5]);
+    }
+
+    #[test]
+    fn test_resize_threshold_grow_u32() {
+        let mut empty = PowerSumQuackU32::new(THRESHOLD);
+        empty.resize_threshold(THRESHOLD + 2).unwrap();
+        assert_eq!(empty.threshold(), THRESHOLD + 2);
+
+        // the count is zero but the quACK is not empty
+        let mut quack = quack![1; threshold = THRESHOLD];
+        quack.remove(2);
+        assert_eq!(
+            quack.resize_threshold(THRESHOLD + 1).unwrap_err(),
+            Error::CannotGrow { count: 0 }
+        );
+
+        let mut quack = quack![1, 2, 3; threshold = THRESHOLD];
+        assert_eq!(
+            quack.resize_threshold(THRESHOLD + 1).unwrap_err(),
+            Error::CannotGrow { count: 3 }
+        );
+        assert_eq!(quack.threshold(), THRESHOLD, "unchanged on error");
+        for elements in [&[1, 2][..], &[1, 2, 4], &[1, 2, 3, 3]] {
+            assert_eq!(
+                quack
+                    .resize_threshold_with_elements(THRESHOLD + 2, elements)
+                    .unwrap_err(),
+                Error::CannotGrow { count: 3 }
+            );
+        }
+        quack
+            .resize_threshold_with_elements(THRESHOLD + 2, &[3, 1, 2])
+            .unwrap();
+        assert_eq!(quack.last_value(), Some(3));
+        assert_eq!(
+            bincode::serialize(&quack).unwrap(),
+            bincode::serialize(&quack![1, 2, 3; threshold = THRESHOLD + 2]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_can_decode_u32() {
+        let mut quack = PowerSumQuackU32::new(THRESHOLD);
+        assert!(quack.can_decode());
+        for x in 1..=THRESHOLD as u32 {
+            quack.insert(x);
+            assert!(quack.can_decode());
+        }
+        quack.insert(0);
+        assert!(!quack.can_decode());
+        assert_eq!(
+            quack.try_decode_with_log(&[0, 1, 2, 3]).unwrap_err(),
+            Error::ExceedsThreshold {
+                count: THRESHOLD as u32 + 1,
+                threshold: THRESHOLD
+            }
+        );
+
+        // subtracting more elements than were inserted wraps the count
+        let mut diff = quack![1; threshold = THRESHOLD];
+        diff.sub_assign(quack![1, 2; threshold = THRESHOLD]);
+        assert_eq!(diff.count(), u32::MAX);
+        assert!(!diff.can_decode());
+        assert!(diff.try_to_coeffs().is_err());
+    }
+
+    /// Decodes three elements from a log long enough to be split across
+    /// threads with the `rayon` feature, where one element appears twice.
+    fn check_decode_long_log<Q>(mut log: Vec<Q::Element>)
+    where
+        Q: PowerSumQuack,
+        Q::Element: Copy + PartialEq + Debug,
+    {
+        let missing = [log[7], log[5000], log[log.len() - 1]];
+        let mut quack = Q::new(THRESHOLD);
+        missing.iter().for_each(|&x| quack.insert(x));
+        log.push(missing[0]);
+        let expected = log
+            .iter()
+            .copied()
+            .filter(|x| missing.contains(x))
+            .collect::<Vec<_>>();
+        assert_eq!(expected.len(), 4);
+        assert_eq!(quack.decode_with_log(&log), expected);
+    }
+
+    #[test]
+    fn test_decode_long_log_u32() {
+        check_decode_long_log::<PowerSumQuackU32>((0..10_000).map(|x| x * 7919).collect());
+    }
+
+    #[test]
+    fn test_decode_with_log_indices_u32() {
+        let log = [5, 10, 15, 10, 20, 25];
+        let mut diff = quack![5, 10, 15, 20, 25; threshold = THRESHOLD];
+        diff.sub_assign(quack![5, 15, 25; threshold = THRESHOLD]);
+        let indices = diff.decode_with_log_indices(&log);
+        assert_eq!(indices, vec![1, 3, 4]);
+        assert_eq!(
+            indices.iter().map(|&i| log[i]).collect::<Vec<_>>(),
+            diff.decode_with_log(&log)
+        );
+
+        // an incomplete log only returns the indices of the roots in it
+        assert_eq!(diff.decode_with_log_indices(&[20, 30]), vec![0]);
+        // an empty quACK matches every element of the log
+        let empty = PowerSumQuackU32::new(THRESHOLD);
+        assert_eq!(
+            empty.decode_with_log_indices(&log),
+            (0..log.len()).collect::<Vec<_>>()
+        );
+        assert!(diff.decode_with_log_indices(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_extend_u32() {
+        let values = (1..=20).collect::<Vec<u32>>();
+        for len in 0..=values.len() {
+            let mut expected = PowerSumQuackU32::new(THRESHOLD);
+            values[..len].iter().for_each(|&x| expected.insert(x));
+            let mut quack = PowerSumQuackU32::new(THRESHOLD);
+            quack.extend(&values[..len]);
+            assert_eq!(
+                bincode::serialize(&quack).unwrap(),
+                bincode::serialize(&expected).unwrap(),
+                "{} values",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_iter_u32() {
+        let quack = (1..=5).collect::<PowerSumQuackU32>();
+        assert_eq!(quack.threshold(), crate::builder::DEFAULT_THRESHOLD);
+        assert_eq!(quack.count(), 5);
+        assert_eq!(quack.last_value(), Some(5));
+    }
+
+    #[test]
+    fn test_try_remove_underflow_u32() {
+        let mut quack = PowerSumQuackU32::new(THRESHOLD);
+        assert_eq!(
+            quack.try_remove(1).unwrap_err(),
+            Error::CountUnderflow {
+                count: 0,
+                removed: 1
+            }
+        );
+        assert_eq!(quack.count(), 0);
+        quack.insert(1);
+        assert!(quack.try_remove(1).is_ok());
+        assert_eq!(quack.count(), 0);
+    }
+
+    #[test]
+    fn test_try_decode_exceeds_threshold_u32() {
+        let mut quack = PowerSumQuackU32::new(2);
+        let log = [1, 2, 3];
+        for x in log {
+            quack.insert(x);
+        }
+        let err = Error::ExceedsThreshold {
+            count: 3,
+            threshold: 2,
+        };
+        assert_eq!(quack.try_to_coeffs().unwrap_err(), err);
+        assert_eq!(quack.try_decode_with_log(&log).unwrap_err(), err);
+        quack.remove(3);
+        assert_eq!(quack.try_to_coeffs().unwrap().len(), 2);
+        assert_eq!(quack.try_decode_with_log(&log).unwrap(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_decode_detailed_complete_u32() {
+        let mut quack = PowerSumQuackU32::new(THRESHOLD);
+        quack.insert(10);
+        quack.insert(20);
+        let diff = quack.decode_with_log_detailed(&[5, 10, 15, 20]);
+        assert_eq!(diff.elements, vec![10, 20]);
+        assert_eq!(diff.missing_unexplained, 0);
+        assert!(diff.complete);
+
+        let diff = PowerSumQuackU32::new(THRESHOLD).decode_with_log_detailed(&[1, 2]);
+        assert!(diff.elements.is_empty(), "nothing is missing");
+        assert!(diff.complete);
+    }
+
+    #[test]
+    fn test_decode_detailed_incomplete_u32() {
+        let mut quack = PowerSumQuackU32::new(THRESHOLD);
+        quack.insert(10);
+        quack.insert(20);
+        quack.insert(30);
+        let diff = quack.decode_with_log_detailed(&[10, 30]);
+        assert_eq!(diff.elements, vec![10, 30]);
+        assert_eq!(diff.missing_unexplained, 1);
+        assert!(!diff.complete);
+
+        quack.insert(40);
+        let diff = quack.decode_with_log_detailed(&[10, 20, 30, 40]);
+        assert!(diff.elements.is_empty(), "exceeds threshold");
+        assert_eq!(diff.missing_unexplained, 4);
+        assert!(!diff.complete);
+    }
+
+    #[test]
+    fn test_decode_detailed_counts_u32() {
+        let mut quack = PowerSumQuackU32::new(THRESHOLD);
+        quack.insert(10);
+        quack.insert(10);
         quack.insert(20);
-        quack.insert(30);
-        assert_eq!(quack.count(), 3);
-        assert_eq!(quack.last_value(), Some(30));
-        quack.remove(10);
-        assert_eq!(quack.count(), 2);
-        assert_eq!(quack.last_value(), Some(30));
-        quack.remove(30);
-        assert_eq!(quack.count(), 1);
-        assert_eq!(quack.last_value(), None);
+        let diff = quack.decode_with_log_detailed(&[10, 20, 10, 30]);
+        assert!(diff.complete);
+        let counts = diff.counts();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&10], 2);
+        assert_eq!(counts[&20], 1);
     }
 
     #[test]
-    fn test_quack_to_coeffs_empty_u32() {
-        let quack = PowerSumQuackU32::new(THRESHOLD);
+    fn test_serialize_into_matches_bincode_u32() {
+        let mut quack = PowerSumQuackU32::new(THRESHOLD);
+        let mut buf = [0u8; 64];
+        for x in [0, 10, 20] {
+            if x > 0 {
+                quack.insert(x);
+            }
+            let len = quack.serialize_into(&mut buf).unwrap();
+            assert_eq!(len, quack.serialized_len());
+            assert_eq!(&buf[..len], &bincode::serialize(&quack).unwrap()[..]);
+        }
+        quack.remove(20);
+        let len = quack.serialize_into(&mut buf).unwrap();
+        assert_eq!(&buf[..len], &bincode::serialize(&quack).unwrap()[..]);
         assert_eq!(
-            quack.to_coeffs(),
-            CoefficientVector::<ModularInteger<u32>>::new()
+            quack.serialize_into(&mut buf[..len - 1]),
+            Err(Error::BufferTooSmall { needed: len })
         );
-        let mut coeffs = vec![];
-        quack.to_coeffs_preallocated(&mut coeffs);
-        assert_eq!(coeffs, CoefficientVector::<ModularInteger<u32>>::new());
     }
 
     #[test]
-    fn test_quack_to_coeffs_small_u32() {
-        const R1: u32 = 1;
-        const R2: u32 = 2;
-
+    fn test_deserialize_into_u32() {
+        let mut expected = PowerSumQuackU32::new(THRESHOLD);
+        expected.insert(10);
+        expected.insert(20);
+        let bytes = bincode::serialize(&expected).unwrap();
         let mut quack = PowerSumQuackU32::new(THRESHOLD);
-        quack.insert(R1);
-        quack.insert(R2);
-        let expected = vec![
-            ModularInteger::<u32>::new(R1 + R2).neg().value(),
-            ModularInteger::<u32>::new(R1 * R2).value(),
-        ]; // x^2 - 3x + 2
+        quack.insert(30);
+        assert_eq!(quack.deserialize_into(&bytes).unwrap(), bytes.len());
+        assert_eq!(bincode::serialize(&quack).unwrap(), bytes);
 
-        assert_eq!(quack.to_coeffs(), expected);
-        let mut coeffs = (0..quack.count()).map(|_| ModularInteger::new(0)).collect();
-        quack.to_coeffs_preallocated(&mut coeffs);
-        assert_eq!(coeffs, expected);
+        // the remaining bytes are not read
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert_eq!(quack.deserialize_into(&longer).unwrap(), bytes.len());
     }
 
     #[test]
-    fn test_quack_to_coeffs_big_u32() {
-        const R1: u64 = 3616712547;
-        const R2: u64 = 2333013068;
-        const R3: u64 = 2234311686;
-        let modulus = ModularInteger::<u32>::modulus_big();
-
+    fn test_deserialize_into_errors_u32() {
+        let mut sent = PowerSumQuackU32::new(THRESHOLD);
+        sent.insert(10);
+        let bytes = bincode::serialize(&sent).unwrap();
         let mut quack = PowerSumQuackU32::new(THRESHOLD);
-        quack.insert(R1 as u32);
-        quack.insert(R2 as u32);
-        quack.insert(R3 as u32);
-        let expected = vec![
-            ModularInteger::<u32>::new(((R1 + R2 + R3) % modulus) as u32)
-                .neg()
-                .value(),
-            ModularInteger::<u32>::new(((R1 * R2 % modulus + R2 * R3 + R1 * R3) % modulus) as u32)
-                .value(),
-            ModularInteger::<u32>::new(((((R1 * R2) % modulus) * R3) % modulus) as u32)
-                .neg()
-                .value(),
-        ];
+        quack.insert(30);
+        let before = bincode::serialize(&quack).unwrap();
 
-        assert_eq!(quack.to_coeffs(), expected);
-        let mut coeffs = (0..quack.count()).map(|_| ModularInteger::new(0)).collect();
-        quack.to_coeffs_preallocated(&mut coeffs);
-        assert_eq!(coeffs, expected);
+        for len in 0..bytes.len() {
+            match quack.deserialize_into(&bytes[..len]) {
+                Err(Error::BufferTooSmall { needed }) => assert!(needed > len),
+                result => panic!("unexpected {:?} for {} bytes", result, len),
+            }
+        }
+        let mut bad = bytes.clone();
+        bad[8 + 4 * THRESHOLD] = 2;
+        assert!(matches!(
+            quack.deserialize_into(&bad),
+            Err(Error::MalformedBuffer(_))
+        ));
+        // the last power sum and the last value are not in the field
+        for offset in [8 + 4 * (THRESHOLD - 1), 8 + 4 * THRESHOLD + 1] {
+            let mut bad = bytes.clone();
+            bad[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+            assert!(bincode::deserialize::<PowerSumQuackU32>(&bad).is_err());
+            assert!(matches!(
+                quack.deserialize_into(&bad),
+                Err(Error::MalformedBuffer(_))
+            ));
+        }
+        let other = bincode::serialize(&PowerSumQuackU32::new(THRESHOLD + 1)).unwrap();
+        assert_eq!(
+            quack.deserialize_into(&other),
+            Err(Error::ThresholdMismatch {
+                expected: THRESHOLD,
+                actual: THRESHOLD + 1
+            })
+        );
+        assert_eq!(bincode::serialize(&quack).unwrap(), before, "unchanged");
     }
 
     #[test]
-    fn test_decode_empty_u32() {
-        let quack = PowerSumQuackU32::new(THRESHOLD);
-        assert_eq!(quack.decode_with_log(&[]), vec![]);
-        assert_eq!(quack.decode_with_log(&[1]), vec![1]);
+    fn test_deserialize_bounded_u32() {
+        let mut expected = PowerSumQuackU32::new(THRESHOLD);
+        expected.insert(10);
+        let bytes = bincode::serialize(&expected).unwrap();
+        let (quack, len) = PowerSumQuackU32::deserialize_bounded(&bytes, THRESHOLD).unwrap();
+        assert_eq!(len, bytes.len());
+        assert_eq!(bincode::serialize(&quack).unwrap(), bytes);
+
+        assert!(matches!(
+            PowerSumQuackU32::deserialize_bounded(&bytes, THRESHOLD - 1),
+            Err(Error::MalformedBuffer(_))
+        ));
+        let zero = bincode::serialize(&PowerSumQuackU32::new(0)).unwrap();
+        assert_eq!(
+            PowerSumQuackU32::deserialize_bounded(&zero, THRESHOLD).unwrap_err(),
+            Error::ZeroThreshold
+        );
+
+        // a huge threshold is rejected without allocating
+        let mut huge = bytes.clone();
+        huge[..8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(
+            PowerSumQuackU32::deserialize_bounded(&huge, usize::MAX).unwrap_err(),
+            Error::BufferTooSmall { needed: usize::MAX }
+        );
+        huge[..8].copy_from_slice(&(1u64 << 40).to_le_bytes());
+        assert_eq!(
+            PowerSumQuackU32::deserialize_bounded(&huge, usize::MAX).unwrap_err(),
+            Error::BufferTooSmall {
+                needed: 8 + 4 * (1 << 40) + 5
+            }
+        );
     }
 
     #[test]
-    fn test_insert_and_decode_u32() {
-        const R1: u32 = 3616712547;
-        const R2: u32 = 2333013068;
-        const R3: u32 = 2234311686;
-        const R4: u32 = 448751902;
-        const R5: u32 = 918748965;
-
-        let mut quack = PowerSumQuackU32::new(THRESHOLD);
-        quack.insert(R1);
-        quack.insert(R2);
-        quack.insert(R3);
-
-        // different orderings
-        // NOTE: the spec of `decode_with_log` doesn't guarantee an order but
-        // here we assume the elements appear in the same order as the list.
-        assert_eq!(quack.decode_with_log(&[R1, R2, R3]), vec![R1, R2, R3]);
-        assert_eq!(quack.decode_with_log(&[R3, R1, R2]), vec![R3, R1, R2]);
+    fn test_insert_batch_u32() {
+        let values = (0..20)
+            .map(|i| u32::MAX - 3 * i)
+            .chain([0, 1, 4_294_967_291, 7])
+            .collect::<Vec<_>>();
+        for len in 0..=values.len() {
+            let mut expected = PowerSumQuackU32::new(THRESHOLD);
+            expected.insert(99);
+            let mut quack = expected.clone();
+            values[..len].iter().for_each(|&x| expected.insert(x));
+            quack.insert_batch(&values[..len]);
+            assert_eq!(
+                bincode::serialize(&quack).unwrap(),
+                bincode::serialize(&expected).unwrap(),
+                "{} values",
+                len
+            );
+        }
+    }
 
-        // one extra element in log
-        assert_eq!(quack.decode_with_log(&[R1, R2, R3, R4]), vec![R1, R2, R3]);
-        assert_eq!(quack.decode_with_log(&[R1, R4, R2, R3]), vec![R1, R2, R3]);
-        assert_eq!(quack.decode_with_log(&[R4, R1, R2, R3]), vec![R1, R2, R3]);
+    #[test]
+    fn test_insert_batch_decode_u32() {
+        let values = (1..=1000).collect::<Vec<u32>>();
+        let mut sent = PowerSumQuackU32::new(THRESHOLD);
+        sent.insert_batch(&values);
+        let mut received = PowerSumQuackU32::new(THRESHOLD);
+        received.insert_batch(&values[2..999]);
+        sent.sub_assign(received);
+        assert_eq!(sent.decode_with_log(&values), vec![1, 2, 1000]);
+    }
 
-        // two extra elements in log
+    #[test]
+    fn test_serialize_compact_u32() {
+        let mut buf = [0u8; 64];
+        for quack in [
+            PowerSumQuackU32::new(THRESHOLD),
+            quack![10, 20; threshold = THRESHOLD],
+            quack![u32::MAX; threshold = THRESHOLD],
+        ] {
+            let len = quack.serialize_compact(&mut buf).unwrap();
+            assert_eq!(len, quack.serialized_compact_len());
+            let (actual, read) = PowerSumQuackU32::deserialize_compact(&buf, THRESHOLD).unwrap();
+            assert_eq!(read, len);
+            assert_eq!(
+                bincode::serialize(&actual).unwrap(),
+                bincode::serialize(&quack).unwrap()
+            );
+        }
+        // the threshold, power sums, option tag, and count take a byte each
+        let empty = PowerSumQuackU32::new(THRESHOLD);
+        assert_eq!(empty.serialized_compact_len(), THRESHOLD + 3);
         assert_eq!(
-            quack.decode_with_log(&[R1, R5, R2, R3, R4]),
-            vec![R1, R2, R3]
+            empty.serialize_compact(&mut buf[..THRESHOLD + 2]),
+            Err(Error::BufferTooSmall {
+                needed: THRESHOLD + 3
+            })
         );
-
-        // not all roots are in log
-        assert_eq!(quack.decode_with_log(&[R1, R2]), vec![R1, R2]);
-        assert_eq!(quack.decode_with_log(&[]), vec![]);
-        assert_eq!(quack.decode_with_log(&[R1, R2, R4]), vec![R1, R2]);
     }
 
     #[test]
-    fn test_remove_and_decode_u32() {
-        const R1: u32 = 3616712547;
-        const R2: u32 = 2333013068;
-        const R3: u32 = 2234311686;
-        const R4: u32 = 448751902;
-        const R5: u32 = 918748965;
-
-        let mut quack = PowerSumQuackU32::new(THRESHOLD);
-        quack.insert(R5);
-        quack.insert(R4);
-        quack.insert(R3);
-        quack.insert(R2);
-        quack.insert(R1);
-        quack.remove(R5);
-        quack.remove(R4);
+    fn test_deserialize_compact_malformed_u32() {
+        let mut bytes = vec![0u8; 64];
+        let quack = quack![10, 20; threshold = THRESHOLD];
+        let len = quack.serialize_compact(&mut bytes).unwrap();
+        bytes.truncate(len);
+        for len in 0..bytes.len() {
+            assert!(matches!(
+                PowerSumQuackU32::deserialize_compact(&bytes[..len], THRESHOLD),
+                Err(Error::BufferTooSmall { .. })
+            ));
+        }
+        assert_eq!(
+            PowerSumQuackU32::deserialize_compact(&[0, 0, 0, 0], THRESHOLD).unwrap_err(),
+            Error::ZeroThreshold
+        );
+        assert!(matches!(
+            PowerSumQuackU32::deserialize_compact(&bytes, THRESHOLD - 1),
+            Err(Error::MalformedBuffer(_))
+        ));
+        // a huge threshold is rejected before allocating
+        let huge = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+        assert!(matches!(
+            PowerSumQuackU32::deserialize_compact(&huge, usize::MAX),
+            Err(Error::BufferTooSmall { .. })
+        ));
+
+        // a power sum outside the field, and a count that overflows a u32
+        let mut buf = [0u8; 16];
+        let mut offset = varint::write(&mut buf, 0, 1);
+        offset = varint::write(&mut buf, offset, ModularInteger::<u32>::modulus().into());
+        assert!(matches!(
+            PowerSumQuackU32::deserialize_compact(&buf[..offset + 2], 1),
+            Err(Error::MalformedBuffer(_))
+        ));
+        let mut offset = varint::write(&mut buf, 0, 1);
+        offset = varint::write(&mut buf, offset, 0);
+        buf[offset] = 0;
+        offset = varint::write(&mut buf, offset + 1, u64::from(u32::MAX) + 1);
+        assert!(matches!(
+            PowerSumQuackU32::deserialize_compact(&buf[..offset], 1),
+            Err(Error::MalformedBuffer(_))
+        ));
+    }
 
-        // R4 and R5 are removed, and R1,2,3 are able to be decoded.
-        // NOTE: the spec of `decode_with_log` doesn't guarantee an order but
-        // here we assume the elements appear in the same order as the list.
-        assert_eq!(quack.decode_with_log(&[R1, R2, R3]), vec![R1, R2, R3]);
+    /// Applies the delta from `prev` to `next` to a copy of `prev`.
+    fn check_delta(prev: &PowerSumQuackU32, next: &PowerSumQuackU32) -> usize {
+        let mut buf = [0u8; 64];
+        let len = next.serialize_delta(prev, &mut buf).unwrap();
+        assert_eq!(len, next.serialized_delta_len(prev));
+        let mut quack = prev.clone();
+        assert_eq!(quack.apply_delta(&buf[..len]), Ok(len));
         assert_eq!(
-            quack.decode_with_log(&[R1, R5, R2, R3, R4]),
-            vec![R1, R2, R3]
+            bincode::serialize(&quack).unwrap(),
+            bincode::serialize(next).unwrap()
         );
+        len
     }
 
     #[test]
-    fn test_decode_with_multiplicity_u32() {
-        const R1: u32 = 10;
-        const R2: u32 = 20;
+    fn test_serialize_delta_u32() {
+        let empty = PowerSumQuackU32::new(THRESHOLD);
+        let prev = quack![1, 2; threshold = THRESHOLD];
+        let next = quack![1, 2, 3; threshold = THRESHOLD];
+        // the threshold, bitmap, count, and tag take a byte each
+        assert_eq!(check_delta(&prev, &prev), 4);
+        assert_eq!(check_delta(&prev, &next), 4 + 4 * THRESHOLD + 4);
+        check_delta(&next, &prev);
+        check_delta(&empty, &next);
+        check_delta(&next, &empty);
+
+        // the count wraps like sub_assign
+        let mut diff = quack![1; threshold = THRESHOLD];
+        diff.sub_assign(quack![1, 2; threshold = THRESHOLD]);
+        check_delta(&empty, &diff);
+
+        // a quACK with the same power sums but a different last value
+        let mut reordered = quack![2, 1; threshold = THRESHOLD];
+        assert_eq!(check_delta(&prev, &reordered), 4 + 4);
+        reordered.last_value = None;
+        assert_eq!(check_delta(&prev, &reordered), 4);
+
+        let other = PowerSumQuackU32::new(THRESHOLD + 1);
+        assert_eq!(
+            other.serialize_delta(&prev, &mut [0; 64]),
+            Err(Error::ThresholdMismatch {
+                expected: THRESHOLD + 1,
+                actual: THRESHOLD
+            })
+        );
+    }
 
-        let mut quack = PowerSumQuackU32::new(THRESHOLD);
-        quack.insert(R1);
-        quack.insert(R1);
+    #[test]
+    fn test_apply_delta_malformed_u32() {
+        let prev = quack![1, 2; threshold = THRESHOLD];
+        let next = quack![1, 2, 3; threshold = THRESHOLD];
+        let mut bytes = vec![0u8; 64];
+        let len = next.serialize_delta(&prev, &mut bytes).unwrap();
+        bytes.truncate(len);
+        let before = bincode::serialize(&prev).unwrap();
+        let check_err = |bytes: &[u8]| {
+            let mut quack = prev.clone();
+            let result = quack.apply_delta(bytes);
+            assert_eq!(bincode::serialize(&quack).unwrap(), before);
+            result.unwrap_err()
+        };
+
+        for len in 0..bytes.len() {
+            assert!(matches!(
+                check_err(&bytes[..len]),
+                Error::BufferTooSmall { .. }
+            ));
+        }
+        let mut quack = PowerSumQuackU32::new(THRESHOLD + 1);
+        assert_eq!(
+            quack.apply_delta(&bytes),
+            Err(Error::ThresholdMismatch {
+                expected: THRESHOLD + 1,
+                actual: THRESHOLD
+            })
+        );
 
-        assert_eq!(quack.decode_with_log(&[R1, R1]), vec![R1, R1]);
-        assert_eq!(quack.decode_with_log(&[R1]), vec![R1]);
-        assert_eq!(quack.decode_with_log(&[R1, R1, R1]), vec![R1, R1, R1]); // even though one R1 is not in the quACK
-        assert_eq!(quack.decode_with_log(&[R1, R1, R2]), vec![R1, R1]);
-        assert_eq!(quack.decode_with_log(&[R2, R1, R2]), vec![R1]);
+        let mut bad = bytes.clone();
+        bad[1] |= 1 << THRESHOLD;
+        assert!(matches!(check_err(&bad), Error::MalformedBuffer(_)));
+        let mut bad = bytes.clone();
+        bad[len - 5] = 3;
+        assert!(matches!(check_err(&bad), Error::MalformedBuffer(_)));
+        // the last power sum is outside the field
+        let mut bad = bytes;
+        bad[2 + 4 * (THRESHOLD - 1)..2 + 4 * THRESHOLD].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(check_err(&bad), Error::MalformedBuffer(_)));
     }
 
     #[test]
-    fn test_subtract_quacks_with_zero_difference_u32() {
-        let mut q1 = PowerSumQuackU32::new(THRESHOLD);
-        q1.insert(1);
-        q1.insert(2);
-        q1.insert(3);
-        q1.insert(4);
-        q1.insert(5);
+    fn test_serialize_with_checksum_u32() {
+        let quack = quack![10, 20; threshold = THRESHOLD];
+        let mut bytes = vec![0u8; 64];
+        let len = quack.serialize_with_checksum(&mut bytes).unwrap();
+        assert_eq!(len, quack.serialized_len() + 4);
+        bytes.truncate(len);
+        let (actual, read) =
+            PowerSumQuackU32::deserialize_with_checksum(&bytes, THRESHOLD).unwrap();
+        assert_eq!(read, len);
+        assert_eq!(
+            bincode::serialize(&actual).unwrap(),
+            bincode::serialize(&quack).unwrap()
+        );
+        assert_eq!(
+            quack.serialize_with_checksum(&mut [0; 8]),
+            Err(Error::BufferTooSmall { needed: len })
+        );
 
-        let quack = q1.clone().sub(q1);
-        assert_eq!(quack.threshold(), THRESHOLD);
-        assert_eq!(quack.count(), 0);
-        assert_eq!(quack.last_value(), None);
+        // a corrupted power sum, last value, or count is detected
+        for i in 8..len {
+            let mut bad = bytes.clone();
+            bad[i] ^= 0x01;
+            let result = PowerSumQuackU32::deserialize_with_checksum(&bad, THRESHOLD);
+            if i == 8 + 4 * THRESHOLD {
+                // the option tag, which changes where the checksum is
+                assert!(result.is_err());
+            } else {
+                assert!(matches!(result, Err(Error::ChecksumMismatch { .. })));
+            }
+        }
         assert_eq!(
-            quack.to_coeffs(),
-            CoefficientVector::<ModularInteger<u32>>::new()
+            PowerSumQuackU32::deserialize_with_checksum(&bytes[..len - 1], THRESHOLD).unwrap_err(),
+            Error::BufferTooSmall { needed: len }
         );
     }
 
     #[test]
-    fn test_subtract_quacks_with_nonzero_difference_u32() {
-        let mut q1 = PowerSumQuackU32::new(THRESHOLD);
-        q1.insert(1);
-        q1.insert(2);
-        q1.insert(3);
-        q1.insert(4);
-        q1.insert(5);
+    fn test_try_deserialize_u32() {
+        let mut expected = PowerSumQuackU32::new(THRESHOLD);
+        expected.insert(10);
+        expected.insert(20);
+        let bytes = bincode::serialize(&expected).unwrap();
+        let quack = PowerSumQuackU32::try_deserialize(&bytes).unwrap();
+        assert_eq!(bincode::serialize(&quack).unwrap(), bytes);
+
+        for len in 0..bytes.len() {
+            assert!(matches!(
+                PowerSumQuackU32::try_deserialize(&bytes[..len]),
+                Err(Error::BufferTooSmall { .. })
+            ));
+        }
+        let trailing = [&bytes[..], &[0]].concat();
+        assert_eq!(
+            PowerSumQuackU32::try_deserialize(&trailing).unwrap_err(),
+            Error::MalformedBuffer("1 trailing bytes".to_string())
+        );
+        let mut bad_tag = bytes.clone();
+        bad_tag[8 + 4 * THRESHOLD] = 2;
+        assert!(matches!(
+            PowerSumQuackU32::try_deserialize(&bad_tag),
+            Err(Error::MalformedBuffer(_))
+        ));
+
+        // a power sum that is not less than the modulus, which
+        // bincode::deserialize also rejects
+        let mut out_of_field = bytes.clone();
+        let modulus = ModularInteger::<u32>::modulus();
+        out_of_field[8..12].copy_from_slice(&modulus.to_le_bytes());
+        assert!(bincode::deserialize::<PowerSumQuackU32>(&out_of_field).is_err());
+        assert_eq!(
+            PowerSumQuackU32::try_deserialize(&out_of_field).unwrap_err(),
+            Error::MalformedBuffer(format!("{modulus} is not less than the modulus"))
+        );
+        out_of_field[8..12].copy_from_slice(&(modulus - 1).to_le_bytes());
+        assert!(PowerSumQuackU32::try_deserialize(&out_of_field).is_ok());
+
+        // the threshold is bounded by the precomputed tables
+        let large = PowerSumQuackU32::new(precompute::max_threshold() + 1);
+        let bytes = bincode::serialize(&large).unwrap();
+        assert!(matches!(
+            PowerSumQuackU32::try_deserialize(&bytes),
+            Err(Error::MalformedBuffer(_))
+        ));
+    }
 
-        let mut q2 = PowerSumQuackU32::new(THRESHOLD);
-        q2.insert(1);
-        q2.insert(2);
+    /// The name and value of each field of each event.
+    #[cfg(feature = "tracing")]
+    type Events = std::sync::Arc<std::sync::Mutex<Vec<Vec<(String, String)>>>>;
 
-        let quack = q1.sub(q2);
-        assert_eq!(quack.threshold(), THRESHOLD);
-        assert_eq!(quack.count(), 3);
-        assert_eq!(quack.last_value(), None);
-        assert_eq!(quack.to_coeffs().len(), 3);
-        assert_eq!(quack.decode_with_log(&[1, 2, 3, 4, 5]), vec![3, 4, 5]);
+    /// A subscriber that records the fields of each event.
+    #[cfg(feature = "tracing")]
+    struct CaptureEvents(Events);
+
+    #[cfg(feature = "tracing")]
+    impl tracing::Subscriber for CaptureEvents {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            tracing::span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, event: &tracing::Event<'_>) {
+            struct Fields(Vec<(String, String)>);
+            impl tracing::field::Visit for Fields {
+                fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn Debug) {
+                    self.0
+                        .push((field.name().to_string(), format!("{:?}", value)));
+                }
+            }
+            let mut fields = Fields(vec![]);
+            event.record(&mut fields);
+            self.0.lock().unwrap().push(fields.0);
+        }
+
+        fn enter(&self, _: &tracing::span::Id) {}
+
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_trace_decode_failure_u32() {
+        let events = Events::default();
+        let subscriber = CaptureEvents(events.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            let mut quack = PowerSumQuackU32::new(2);
+            for x in [1, 2, 3] {
+                quack.insert(x);
+            }
+            assert!(quack.try_decode_with_log(&[1, 2, 3]).is_err());
+            quack.remove(3);
+            assert_eq!(quack.decode_with_log(&[1]), vec![1]);
+        });
+        let field = |name: &str, value: &str| (name.to_string(), value.to_string());
+        let events = events.lock().unwrap();
+        assert!(events.contains(&vec![
+            field("message", "quACK exceeds threshold"),
+            field("count", "3"),
+            field("threshold", "2"),
+        ]));
+        assert!(events.contains(&vec![
+            field("message", "decoded fewer elements than the quACK count"),
+            field("count", "2"),
+            field("decoded", "1"),
+        ]));
     }
 
     #[test]
@@ -1350,4 +3292,68 @@ mod test {
         assert_eq!(quack.to_coeffs().len(), 3);
         assert_eq!(quack.decode_with_log(&[1, 2, 3, 4, 5]), vec![3, 4, 5]);
     }
+
+    #[test]
+    #[cfg(feature = "power_table")]
+    fn test_add_quacks_u16() {
+        let mut q1 = PowerSumQuackU16::new(THRESHOLD);
+        q1.insert(1);
+        q1.insert(2);
+
+        let mut q2 = PowerSumQuackU16::new(THRESHOLD);
+        q2.insert(3);
+        q2.insert(4);
+        q2.insert(5);
+
+        let merged = q1.merge(&q2);
+        assert_eq!(merged.threshold(), THRESHOLD);
+        assert_eq!(merged.count(), 5);
+        assert_eq!(merged.last_value(), Some(5));
+
+        let mut q3 = PowerSumQuackU16::new(THRESHOLD);
+        for x in 1..=6 {
+            q3.insert(x);
+        }
+        let quack = q3.sub(merged);
+        assert_eq!(quack.count(), 1);
+        assert_eq!(quack.decode_with_log(&[1, 2, 3, 4, 5, 6]), vec![6]);
+    }
+
+    #[test]
+    #[cfg(feature = "montgomery")]
+    fn test_add_quacks_u64() {
+        let mut q1 = PowerSumQuackU64::new(THRESHOLD);
+        q1.insert(1);
+        q1.insert(2);
+
+        let mut q2 = PowerSumQuackU64::new(THRESHOLD);
+        q2.insert(3);
+        q2.insert(4);
+        q2.insert(5);
+
+        let merged = q1.merge(&q2);
+        assert_eq!(merged.threshold(), THRESHOLD);
+        assert_eq!(merged.count(), 5);
+        assert_eq!(merged.last_value(), Some(5));
+
+        let mut q3 = PowerSumQuackU64::new(THRESHOLD);
+        for x in 1..=6 {
+            q3.insert(x);
+        }
+        let quack = q3.sub(merged);
+        assert_eq!(quack.count(), 1);
+        assert_eq!(quack.decode_with_log(&[1, 2, 3, 4, 5, 6]), vec![6]);
+    }
+
+    #[test]
+    #[cfg(feature = "power_table")]
+    fn test_decode_long_log_u16() {
+        check_decode_long_log::<PowerSumQuackU16>((0..10_000).collect());
+    }
+
+    #[test]
+    #[cfg(feature = "montgomery")]
+    fn test_decode_long_log_u64() {
+        check_decode_long_log::<PowerSumQuackU64>((0..10_000).map(|x| x << 32 | x).collect());
+    }
 }
@@ -1,4 +1,5 @@
 use crate::arithmetic::{self, ModularArithmetic, ModularInteger, CoefficientVector};
+use crate::error::Error;
 use crate::precompute::{INVERSE_TABLE_U16, POWER_TABLE};
 use crate::PowerSumQuack;
 use serde::{Deserialize, Serialize};
@@ -104,16 +105,24 @@ impl PowerSumQuack for PowerTableQuack {
     }
 
     fn sub_assign(&mut self, rhs: Self) {
-        assert_eq!(
-            self.threshold(),
-            rhs.threshold(),
-            "expected subtracted quacks to have the same threshold"
-        );
+        if let Err(e) = self.try_sub_assign(rhs) {
+            panic!("{}", e);
+        }
+    }
+
+    fn try_sub_assign(&mut self, rhs: Self) -> Result<(), Error> {
+        if self.threshold() != rhs.threshold() {
+            return Err(Error::ThresholdMismatch {
+                expected: self.threshold(),
+                actual: rhs.threshold(),
+            });
+        }
         for (i, sum) in self.power_sums.iter_mut().enumerate() {
             sum.sub_assign(rhs.power_sums[i]);
         }
         self.count = self.count.wrapping_sub(rhs.count);
         self.last_value = None;
+        Ok(())
     }
 
     fn sub(self, rhs: Self) -> Self {
@@ -121,6 +130,21 @@ impl PowerSumQuack for PowerTableQuack {
         result.sub_assign(rhs);
         result
     }
+
+    fn try_add_assign(&mut self, rhs: &Self) -> Result<(), Error> {
+        if self.threshold() != rhs.threshold() {
+            return Err(Error::ThresholdMismatch {
+                expected: self.threshold(),
+                actual: rhs.threshold(),
+            });
+        }
+        for (sum, &rhs_sum) in self.power_sums.iter_mut().zip(&rhs.power_sums) {
+            sum.add_assign(rhs_sum);
+        }
+        self.count = self.count.wrapping_add(rhs.count);
+        self.last_value = rhs.last_value.or(self.last_value);
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -339,4 +363,29 @@ mod test {
         assert_eq!(quack.to_coeffs().len(), 3);
         assert_eq!(quack.decode_with_log(&[1, 2, 3, 4, 5]), vec![3, 4, 5]);
     }
+
+    #[test]
+    fn test_add_quacks_u16() {
+        let mut q1 = PowerTableQuack::new(THRESHOLD);
+        q1.insert(1);
+        q1.insert(2);
+
+        let mut q2 = PowerTableQuack::new(THRESHOLD);
+        q2.insert(3);
+        q2.insert(4);
+        q2.insert(5);
+
+        let merged = q1.merge(&q2);
+        assert_eq!(merged.threshold(), THRESHOLD);
+        assert_eq!(merged.count(), 5);
+        assert_eq!(merged.last_value(), Some(5));
+
+        let mut q3 = PowerTableQuack::new(THRESHOLD);
+        for x in 1..=6 {
+            q3.insert(x);
+        }
+        let quack = q3.sub(merged);
+        assert_eq!(quack.count(), 1);
+        assert_eq!(quack.decode_with_log(&[1, 2, 3, 4, 5, 6]), vec![6]);
+    }
 }
@@ -1,5 +1,6 @@
 //! Efficient multiplicative modular inverses.
 use crate::arithmetic::{ModularArithmetic, ModularInteger};
+use crate::error::Error;
 use once_cell::sync::Lazy;
 
 cfg_montgomery! {
@@ -13,10 +14,12 @@ pub(crate) static mut MAX_THRESHOLD: usize = 20;
 /// The multiplicative modular inverses of the integers up to this threshold
 /// are lazily precomputed, for more efficient divison. This function MUST be
 /// called before modifying any quACKs with a threshold greater than the default
-/// threshold of `20`, and immediately precomputes the tables.
+/// threshold of `20`, and immediately precomputes the table for
+/// [crate::PowerSumQuackU32].
 ///
 /// If this function is not called, the code may panic when trying to access a
-/// modular inverse that is out of range. This function should also be called
+/// modular inverse that is out of range. [crate::QuackBuilder] calls this
+/// function if needed. This function should also be called
 /// if the known maximum threshold is less than the default, to improve cache
 /// performance.
 pub fn global_config_set_max_power_sum_threshold(threshold: usize) {
@@ -24,16 +27,53 @@ pub fn global_config_set_max_power_sum_threshold(threshold: usize) {
         MAX_THRESHOLD = threshold;
     }
 
-    // precompute all tables
+    // precompute the table of the default quACK type, the tables of the
+    // other types are computed the first time they are used
     let _ = INVERSE_TABLE_U32[0];
-    // cfg_montgomery! {
-    //     let _ = INVERSE_TABLE_U64[0];
-    //     let _ = INVERSE_TABLE_MONTGOMERY[0];
-    // }
-    // cfg_power_table! {
-    //     let _ = INVERSE_TABLE_U16[0];
-    //     let _ = POWER_TABLE[0];
-    // }
+}
+
+/// The maximum threshold set by [global_config_set_max_power_sum_threshold].
+pub(crate) fn max_threshold() -> usize {
+    unsafe { MAX_THRESHOLD }
+}
+
+/// The maximum threshold supported by the tables that have already been
+/// computed, or `None` if no tables have been computed. The tables that have
+/// not been computed yet will support the maximum threshold at the time they
+/// are first used.
+fn computed_max_threshold() -> Option<usize> {
+    #[allow(unused_mut)]
+    let mut lens = vec![Lazy::get(&INVERSE_TABLE_U32).map(Vec::len)];
+    #[cfg(any(feature = "montgomery", doc))]
+    lens.extend([
+        Lazy::get(&INVERSE_TABLE_U64).map(Vec::len),
+        Lazy::get(&INVERSE_TABLE_MONTGOMERY).map(Vec::len),
+    ]);
+    #[cfg(any(feature = "power_table", doc))]
+    lens.extend([
+        Lazy::get(&INVERSE_TABLE_U16).map(Vec::len),
+        Lazy::get(&POWER_TABLE).map(|table| table[0].len() - 1),
+    ]);
+    lens.into_iter().flatten().min()
+}
+
+/// Makes sure the tables support quACKs with the threshold, raising the
+/// maximum threshold if it is smaller. Returns [Error::ThresholdTooLarge] if
+/// the tables were already computed with a smaller maximum.
+pub(crate) fn ensure_max_threshold(threshold: usize) -> Result<(), Error> {
+    if let Some(max) = computed_max_threshold().filter(|&max| max < threshold) {
+        trace_event!(
+            WARN,
+            threshold,
+            max,
+            "threshold exceeds the precomputed tables"
+        );
+        return Err(Error::ThresholdTooLarge { threshold, max });
+    }
+    if threshold > max_threshold() {
+        global_config_set_max_power_sum_threshold(threshold);
+    }
+    Ok(())
 }
 
 /// Multiplication by the `i`-th term corresponds to division by the integer
@@ -0,0 +1,25 @@
+//! Imports the quACK traits and types available under the enabled features.
+//!
+//! ```
+//! use quack::prelude::*;
+//!
+//! let mut quack = PowerSumQuackU32::new(10);
+//! quack.insert(10);
+//! let coeffs = quack.to_coeffs();
+//! assert_eq!(coeffs, vec![ModularInteger::<u32>::new(10).neg()]);
+//! ```
+pub use crate::arithmetic::{ModularArithmetic, ModularInteger};
+pub use crate::{quack, DecodedDiff, Error, PowerSumQuack, PowerSumQuackU32, QuackBuilder};
+
+cfg_montgomery! {
+    pub use crate::arithmetic::MontgomeryInteger;
+    pub use crate::{MontgomeryQuack, PowerSumQuackU64};
+}
+
+cfg_power_table! {
+    pub use crate::{PowerSumQuackU16, PowerTableQuack};
+}
+
+cfg_strawmen! {
+    pub use crate::{StrawmanAQuack, StrawmanBQuack};
+}
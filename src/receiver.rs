@@ -0,0 +1,239 @@
+//! Receiver-side policy for when to emit quACKs.
+use crate::PowerSumQuack;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// When the receiver should emit a quACK.
+///
+/// The policy triggers after a number of packets are inserted since the last
+/// quACK was emitted, after an interval has passed since the last quACK was
+/// emitted, or after whichever comes first. The interval starts when the
+/// first packet is inserted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EmitPolicy {
+    every_packets: Option<u32>,
+    every_interval: Option<Duration>,
+    count: u32,
+    last_emit_count: u32,
+    last_emit_time: Option<Instant>,
+}
+
+impl EmitPolicy {
+    fn new(every_packets: Option<u32>, every_interval: Option<Duration>) -> Self {
+        Self {
+            every_packets: every_packets.map(|k| k.max(1)),
+            every_interval,
+            count: 0,
+            last_emit_count: 0,
+            last_emit_time: None,
+        }
+    }
+
+    /// Emit a quACK every `k` inserted packets.
+    pub fn every_packets(k: u32) -> Self {
+        Self::new(Some(k), None)
+    }
+
+    /// Emit a quACK every `interval`.
+    pub fn every_interval(interval: Duration) -> Self {
+        Self::new(None, Some(interval))
+    }
+
+    /// Emit a quACK every `k` inserted packets or every `interval`, whichever
+    /// comes first.
+    pub fn hybrid(k: u32, interval: Duration) -> Self {
+        Self::new(Some(k), Some(interval))
+    }
+
+    /// Emit a quACK whenever the number of inserted packets since the last
+    /// quACK reaches half the threshold, so the sender can decode the quACK
+    /// even if up to half of the packets since the previous quACK are lost.
+    pub fn half_threshold(threshold: usize) -> Self {
+        Self::every_packets((threshold / 2) as u32)
+    }
+
+    /// The number of packets inserted since the last emitted quACK.
+    pub fn pending(&self) -> u32 {
+        self.count.wrapping_sub(self.last_emit_count)
+    }
+
+    /// Records that a packet was inserted at time `now`, returning whether to
+    /// emit a quACK.
+    pub fn on_insert(&mut self, now: Instant) -> bool {
+        self.count = self.count.wrapping_add(1);
+        self.last_emit_time.get_or_insert(now);
+        let emit =
+            self.every_packets.is_some_and(|k| self.pending() >= k) || self.interval_elapsed(now);
+        if emit {
+            self.emitted(now);
+        }
+        emit
+    }
+
+    /// Returns whether to emit a quACK at time `now` because the interval has
+    /// passed, even if no packet was inserted. Call this periodically to emit
+    /// time-based quACKs when packets stop arriving.
+    pub fn poll(&mut self, now: Instant) -> bool {
+        let emit = self.interval_elapsed(now);
+        if emit {
+            self.emitted(now);
+        }
+        emit
+    }
+
+    fn interval_elapsed(&self, now: Instant) -> bool {
+        match (self.every_interval, self.last_emit_time) {
+            (Some(interval), Some(last)) => now.saturating_duration_since(last) >= interval,
+            _ => false,
+        }
+    }
+
+    fn emitted(&mut self, now: Instant) {
+        self.last_emit_count = self.count;
+        self.last_emit_time = Some(now);
+    }
+}
+
+/// A receiver that owns a cumulative quACK and emits serialized snapshots of
+/// it according to an [EmitPolicy].
+#[derive(Clone, Debug)]
+pub struct Receiver<Q> {
+    quack: Q,
+    policy: EmitPolicy,
+}
+
+impl<Q: PowerSumQuack + Serialize> Receiver<Q> {
+    /// Creates a receiver with an empty quACK that can decode at most
+    /// `threshold` number of elements.
+    pub fn new(threshold: usize, policy: EmitPolicy) -> Self {
+        Self {
+            quack: Q::new(threshold),
+            policy,
+        }
+    }
+
+    /// The cumulative quACK.
+    pub fn quack(&self) -> &Q {
+        &self.quack
+    }
+
+    /// The emit policy.
+    pub fn policy(&self) -> &EmitPolicy {
+        &self.policy
+    }
+
+    /// Inserts a received packet at time `now`, returning a serialized
+    /// snapshot of the quACK if the policy says to emit one.
+    pub fn insert(&mut self, value: Q::Element, now: Instant) -> Option<Vec<u8>> {
+        self.quack.insert(value);
+        self.policy.on_insert(now).then(|| self.snapshot())
+    }
+
+    /// Returns a serialized snapshot of the quACK if the policy says to emit
+    /// one at time `now` even though no packet was inserted.
+    pub fn poll(&mut self, now: Instant) -> Option<Vec<u8>> {
+        self.policy.poll(now).then(|| self.snapshot())
+    }
+
+    /// A snapshot of the quACK, serialized with `bincode`.
+    pub fn snapshot(&self) -> Vec<u8> {
+        bincode::serialize(&self.quack).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::PowerSumQuackU32;
+
+    fn ms(start: Instant, millis: u64) -> Instant {
+        start + Duration::from_millis(millis)
+    }
+
+    /// Inserts packets at the given times, returning the indexes of the
+    /// packets that triggered a quACK.
+    fn emissions(policy: &mut EmitPolicy, start: Instant, times: &[u64]) -> Vec<usize> {
+        times
+            .iter()
+            .enumerate()
+            .filter(|&(_, &t)| policy.on_insert(ms(start, t)))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    #[test]
+    fn test_every_packets() {
+        let start = Instant::now();
+        let mut policy = EmitPolicy::every_packets(3);
+        let times = (0..10).collect::<Vec<_>>();
+        assert_eq!(emissions(&mut policy, start, &times), vec![2, 5, 8]);
+        assert_eq!(policy.pending(), 1);
+        assert!(!policy.poll(ms(start, 1000)));
+    }
+
+    #[test]
+    fn test_every_interval() {
+        let start = Instant::now();
+        let mut policy = EmitPolicy::every_interval(Duration::from_millis(10));
+        let times = [0, 4, 8, 12, 16, 20, 24];
+        assert_eq!(emissions(&mut policy, start, &times), vec![3, 6]);
+    }
+
+    #[test]
+    fn test_poll_without_arrivals() {
+        let start = Instant::now();
+        let mut policy = EmitPolicy::every_interval(Duration::from_millis(10));
+        assert!(!policy.poll(ms(start, 100)), "no packets inserted yet");
+        assert!(!policy.on_insert(start));
+        assert!(!policy.poll(ms(start, 5)));
+        assert!(policy.poll(ms(start, 10)));
+        assert!(!policy.poll(ms(start, 15)));
+        assert!(policy.poll(ms(start, 20)));
+    }
+
+    #[test]
+    fn test_hybrid() {
+        let start = Instant::now();
+        let mut policy = EmitPolicy::hybrid(4, Duration::from_millis(10));
+
+        // A burst of 6 packets triggers on the packet count. Then a packet
+        // every 3 ms triggers on the packet count at 6 ms, and on the
+        // interval at 16 ms before the packet count is reached again.
+        let times = [0, 0, 0, 0, 0, 0, 3, 6, 9, 12, 15, 16];
+        assert_eq!(emissions(&mut policy, start, &times), vec![3, 7, 11]);
+    }
+
+    #[test]
+    fn test_half_threshold() {
+        let start = Instant::now();
+        let mut policy = EmitPolicy::half_threshold(10);
+        let times = (0..12).collect::<Vec<_>>();
+        assert_eq!(emissions(&mut policy, start, &times), vec![4, 9]);
+
+        // a threshold of 1 emits on every packet rather than never
+        let mut policy = EmitPolicy::half_threshold(1);
+        assert_eq!(emissions(&mut policy, start, &[0, 1]), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_receiver_snapshots() {
+        let start = Instant::now();
+        let mut receiver = Receiver::<PowerSumQuackU32>::new(10, EmitPolicy::every_packets(5));
+        let mut snapshots = vec![];
+        for x in 0..12 {
+            if let Some(bytes) = receiver.insert(x, ms(start, x as u64)) {
+                snapshots.push(bytes);
+            }
+        }
+        assert_eq!(snapshots.len(), 2);
+        let counts = snapshots
+            .iter()
+            .map(|bytes| bincode::deserialize::<PowerSumQuackU32>(bytes).unwrap())
+            .map(|quack| (quack.count(), quack.last_value()))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, vec![(5, Some(4)), (10, Some(9))]);
+        assert_eq!(receiver.quack().count(), 12);
+        assert_eq!(receiver.policy().pending(), 2);
+        assert!(receiver.poll(ms(start, 1000)).is_none());
+    }
+}
@@ -0,0 +1,728 @@
+//! Sender-side bookkeeping for interpreting received quACKs.
+use crate::{PowerSumQuack, PowerSumQuackU32};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// The default maximum number of entries in a [SentLog].
+pub const DEFAULT_CAPACITY: usize = 1 << 16;
+
+/// How a [SentLog] handles an identifier that is recorded while another entry
+/// with the same identifier is still in the log.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IdReuse {
+    /// Record both entries. If only some copies of an identifier are missing,
+    /// the oldest copies are considered missing.
+    #[default]
+    Allow,
+    /// Refuse to record the new entry.
+    Reject,
+}
+
+/// The result of interpreting a received quACK with a [SentLog].
+#[derive(Debug, PartialEq, Eq)]
+pub enum LossReport<T> {
+    /// The quACK was decoded. Acknowledged and lost entries are removed from
+    /// the log, and any other entries are still in flight.
+    Decoded {
+        /// Entries sent before the last packet received by the receiver that
+        /// the receiver is missing, in the order they were recorded.
+        lost: Vec<(u32, T)>,
+        /// Entries the receiver has received, in the order they were recorded.
+        acked: Vec<(u32, T)>,
+    },
+    /// Too many packets are missing to decode the quACK, even assuming every
+    /// packet sent after the last packet received by the receiver is still in
+    /// flight. The log is unchanged.
+    ThresholdExceeded {
+        /// The number of missing packets.
+        num_missing: u32,
+    },
+    /// The received quACK does not have the same threshold as the log. The log
+    /// is unchanged.
+    ThresholdMismatch {
+        /// The threshold of the log.
+        expected: usize,
+        /// The threshold of the received quACK.
+        received: usize,
+    },
+}
+
+/// A log of sent packets that translates the cumulative quACKs sent by the
+/// receiver into the specific transmissions that were acknowledged or lost.
+///
+/// Each entry is a packet identifier and some metadata `T` about the
+/// transmission, e.g., the time it was sent. The log also keeps a cumulative
+/// quACK of the sent packets, so each received quACK must be cumulative over
+/// every packet the receiver has received since the log was created. Lost
+/// packets are removed from the sent quACK so they are only reported once,
+/// which assumes lost packets never arrive at the receiver afterwards.
+///
+/// Entries evicted from a full log are assumed to have been received. If an
+/// evicted packet was actually lost, it takes up one element of the threshold
+/// in every future difference quACK.
+///
+/// # Examples
+///
+/// ```
+/// use quack::prelude::*;
+/// use quack::sender::{LossReport, SentLog};
+///
+/// let mut log = SentLog::new(10);
+/// let mut receiver = PowerSumQuackU32::new(10);
+/// for id in 1..=5 {
+///     log.record(id, format!("packet {}", id)).unwrap();
+///     if id != 3 {
+///         receiver.insert(id);
+///     }
+/// }
+/// match log.on_quack(&receiver) {
+///     LossReport::Decoded { lost, acked } => {
+///         assert_eq!(lost, vec![(3, "packet 3".to_string())]);
+///         assert_eq!(acked.len(), 4);
+///     }
+///     _ => unreachable!(),
+/// }
+/// assert!(log.is_empty());
+/// ```
+#[derive(Clone, Debug)]
+pub struct SentLog<T> {
+    sent: PowerSumQuackU32,
+    entries: VecDeque<(u32, T)>,
+    /// The number of entries with each identifier, to check for reused
+    /// identifiers without scanning the entries.
+    live: HashMap<u32, usize>,
+    capacity: usize,
+    id_reuse: IdReuse,
+}
+
+impl<T> SentLog<T> {
+    /// Creates a new log that can decode at most `threshold` number of
+    /// missing packets, with the default capacity.
+    pub fn new(threshold: usize) -> Self {
+        Self::with_capacity(threshold, DEFAULT_CAPACITY)
+    }
+
+    /// Creates a new log that can decode at most `threshold` number of
+    /// missing packets and holds at most `capacity` entries.
+    pub fn with_capacity(threshold: usize, capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        Self {
+            sent: PowerSumQuackU32::new(threshold),
+            entries: VecDeque::new(),
+            live: HashMap::new(),
+            capacity,
+            id_reuse: IdReuse::default(),
+        }
+    }
+
+    /// Sets how identifiers that are already in the log are handled.
+    pub fn id_reuse(mut self, id_reuse: IdReuse) -> Self {
+        self.id_reuse = id_reuse;
+        self
+    }
+
+    /// The maximum number of missing packets that can be decoded.
+    pub fn threshold(&self) -> usize {
+        self.sent.threshold()
+    }
+
+    /// The maximum number of entries in the log.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of entries that are neither acknowledged nor lost.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether every recorded entry is acknowledged or lost.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterates over the entries that are neither acknowledged nor lost, in
+    /// the order they were recorded.
+    pub fn iter(&self) -> impl Iterator<Item = &(u32, T)> {
+        self.entries.iter()
+    }
+
+    /// Records a sent packet. If the log is full, evicts and returns the
+    /// oldest entry. Returns the metadata as an error if the identifier is
+    /// already in the log and identifiers cannot be reused.
+    pub fn record(&mut self, id: u32, meta: T) -> Result<Option<(u32, T)>, T> {
+        if self.id_reuse == IdReuse::Reject && self.live.contains_key(&id) {
+            return Err(meta);
+        }
+        let evicted = if self.entries.len() == self.capacity {
+            self.entries.pop_front()
+        } else {
+            None
+        };
+        if let Some((evicted_id, _)) = &evicted {
+            release(&mut self.live, *evicted_id);
+        }
+        self.sent.insert(id);
+        self.entries.push_back((id, meta));
+        *self.live.entry(id).or_insert(0) += 1;
+        Ok(evicted)
+    }
+
+    /// Interprets a cumulative quACK received from the receiver.
+    ///
+    /// Subtracts the quACK from the sent quACK and decodes the difference
+    /// against the log. A missing packet is lost if it was sent before the
+    /// last packet received by the receiver, otherwise it is still in flight.
+    /// Packets the receiver has received are acknowledged, even if they were
+    /// received out of order.
+    ///
+    /// If too many packets are missing, retries assuming every packet sent
+    /// after the last packet received by the receiver is still in flight.
+    /// Packets that were reordered around the last received packet cannot be
+    /// acknowledged in this case.
+    pub fn on_quack(&mut self, received: &PowerSumQuackU32) -> LossReport<T> {
+        let threshold = self.threshold();
+        if received.threshold() != threshold {
+            return LossReport::ThresholdMismatch {
+                expected: threshold,
+                received: received.threshold(),
+            };
+        }
+        if received.count() == 0 {
+            return LossReport::Decoded {
+                lost: vec![],
+                acked: vec![],
+            };
+        }
+
+        // The index of the most recent entry with the last received value.
+        let cut = received
+            .last_value()
+            .and_then(|value| self.entries.iter().rposition(|&(id, _)| id == value));
+        let mut diff = self.sent.clone();
+        diff.sub_assign(received.clone());
+        let mut decode_len = self.entries.len();
+        if !diff.can_decode() {
+            let Some(cut) = cut else {
+                return LossReport::ThresholdExceeded {
+                    num_missing: diff.count(),
+                };
+            };
+            for &(id, _) in self.entries.range((cut + 1)..) {
+                diff.remove(id);
+            }
+            if !diff.can_decode() {
+                return LossReport::ThresholdExceeded {
+                    num_missing: diff.count(),
+                };
+            }
+            decode_len = cut + 1;
+        }
+
+        let mut missing = self.decode_missing(&diff, decode_len);
+        let mut lost = vec![];
+        let mut acked = vec![];
+        let mut in_flight = VecDeque::new();
+        for (i, (id, meta)) in self.entries.drain(..).enumerate() {
+            if i >= decode_len {
+                in_flight.push_back((id, meta));
+                continue;
+            }
+            match missing.get_mut(&id) {
+                Some(n) if *n > 0 => {
+                    *n -= 1;
+                    if cut.is_some_and(|cut| i <= cut) {
+                        self.sent.remove(id);
+                        release(&mut self.live, id);
+                        lost.push((id, meta));
+                    } else {
+                        in_flight.push_back((id, meta));
+                    }
+                }
+                _ => {
+                    release(&mut self.live, id);
+                    acked.push((id, meta));
+                }
+            }
+        }
+        self.entries = in_flight;
+        LossReport::Decoded { lost, acked }
+    }
+
+    /// Decodes the difference quACK against the first `decode_len` entries,
+    /// returning the number of times each identifier is missing.
+    fn decode_missing(&self, diff: &PowerSumQuackU32, decode_len: usize) -> HashMap<u32, usize> {
+        let mut missing = HashMap::new();
+        if diff.count() == 0 {
+            return missing;
+        }
+        let mut copies = HashMap::new();
+        let log = self
+            .entries
+            .iter()
+            .take(decode_len)
+            .map(|&(id, _)| {
+                *copies.entry(id).or_insert(0) += 1;
+                id
+            })
+            .collect::<Vec<_>>();
+        for id in diff.decode_with_log(&log) {
+            missing.insert(id, 1);
+        }
+
+        // The decoded roots do not include their multiplicity, so check how
+        // many copies of a reused identifier are in the difference.
+        for (&id, n) in missing.iter_mut() {
+            let mut diff = diff.clone();
+            while *n < copies[&id] {
+                diff.remove(id);
+                if diff.count() == 0 || diff.decode_with_log(&[id]).is_empty() {
+                    break;
+                }
+                *n += 1;
+            }
+        }
+        missing
+    }
+}
+
+/// Removes one entry with the identifier from the count of live entries.
+fn release(live: &mut HashMap<u32, usize>, id: u32) {
+    if let Some(n) = live.get_mut(&id) {
+        *n -= 1;
+        if *n == 0 {
+            live.remove(&id);
+        }
+    }
+}
+
+/// How a [RetxPolicy] classifies a missing packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Classification {
+    /// The packet should be retransmitted.
+    Lost,
+    /// The packet may still arrive, because the quACK may have been sent
+    /// before it arrived or it may have been reordered.
+    TooRecent,
+    /// The packet or the last packet received by the receiver is not in the
+    /// log, so the packet cannot be classified.
+    Unknown,
+}
+
+/// Decides which decoded missing packets to retransmit.
+///
+/// A missing packet is not necessarily lost: the quACK may have been sent
+/// before the packet arrived. A missing packet is lost only if it was sent at
+/// least the reordering threshold number of packets before the last packet
+/// received by the receiver, or if a reordering delay is configured, it was
+/// sent before the last received packet and at least that long ago.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetxPolicy {
+    reorder_threshold: usize,
+    reorder_delay: Option<Duration>,
+}
+
+impl Default for RetxPolicy {
+    /// A reordering threshold of 3 packets, as in TCP fast retransmit, and no
+    /// reordering delay.
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+impl RetxPolicy {
+    /// Creates a policy with the given reordering threshold in packets. A
+    /// threshold of `1` considers every missing packet sent before the last
+    /// received packet lost.
+    pub fn new(reorder_threshold: usize) -> Self {
+        Self {
+            reorder_threshold: reorder_threshold.max(1),
+            reorder_delay: None,
+        }
+    }
+
+    /// Also considers a missing packet lost if it was sent before the last
+    /// received packet at least `delay` ago. Only applies to
+    /// [RetxPolicy::classify_timed].
+    pub fn reorder_delay(mut self, delay: Duration) -> Self {
+        self.reorder_delay = Some(delay);
+        self
+    }
+
+    /// Classifies each missing packet, given the log of sent packets in the
+    /// order they were sent and the last packet received by the receiver,
+    /// i.e., the `last_value()` of its quACK.
+    pub fn classify(
+        &self,
+        log: &[u32],
+        missing: &[u32],
+        last_value: Option<u32>,
+    ) -> Vec<(u32, Classification)> {
+        self.classify_inner(log.iter().copied(), missing, last_value, |_| false)
+    }
+
+    /// Like [RetxPolicy::classify], but the log also contains the time each
+    /// packet was sent, so the reordering delay applies.
+    pub fn classify_timed(
+        &self,
+        log: &[(u32, Instant)],
+        missing: &[u32],
+        last_value: Option<u32>,
+        now: Instant,
+    ) -> Vec<(u32, Classification)> {
+        let expired = |i: usize| match self.reorder_delay {
+            Some(delay) => now.saturating_duration_since(log[i].1) >= delay,
+            None => false,
+        };
+        self.classify_inner(log.iter().map(|&(id, _)| id), missing, last_value, expired)
+    }
+
+    /// The packets to retransmit out of the classified missing packets.
+    pub fn retransmissions(classified: &[(u32, Classification)]) -> Vec<u32> {
+        classified
+            .iter()
+            .filter(|(_, class)| *class == Classification::Lost)
+            .map(|&(id, _)| id)
+            .collect()
+    }
+
+    fn classify_inner(
+        &self,
+        log: impl Iterator<Item = u32>,
+        missing: &[u32],
+        last_value: Option<u32>,
+        expired: impl Fn(usize) -> bool,
+    ) -> Vec<(u32, Classification)> {
+        let log = log.collect::<Vec<_>>();
+        let cut = last_value.and_then(|value| log.iter().rposition(|&id| id == value));
+        missing
+            .iter()
+            .map(|&id| {
+                let Some(cut) = cut else {
+                    return (id, Classification::Unknown);
+                };
+                // Prefer the most recent copy sent before the last received
+                // packet, in case the identifier was reused.
+                let index = log[..cut]
+                    .iter()
+                    .rposition(|&x| x == id)
+                    .or_else(|| log.iter().position(|&x| x == id));
+                let class = match index {
+                    None => Classification::Unknown,
+                    Some(i) if i >= cut => Classification::TooRecent,
+                    Some(i) if cut - i >= self.reorder_threshold || expired(i) => {
+                        Classification::Lost
+                    }
+                    Some(_) => Classification::TooRecent,
+                };
+                (id, class)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const THRESHOLD: usize = 10;
+
+    type Entries<T> = Vec<(u32, T)>;
+
+    fn receive(ids: &[u32]) -> PowerSumQuackU32 {
+        let mut quack = PowerSumQuackU32::new(THRESHOLD);
+        for &id in ids {
+            quack.insert(id);
+        }
+        quack
+    }
+
+    fn decoded<T>(report: LossReport<T>) -> (Entries<T>, Entries<T>) {
+        match report {
+            LossReport::Decoded { lost, acked } => (lost, acked),
+            _ => panic!("quACK was not decoded"),
+        }
+    }
+
+    fn ids<T>(entries: &[(u32, T)]) -> Vec<u32> {
+        entries.iter().map(|&(id, _)| id).collect()
+    }
+
+    #[test]
+    fn test_lost_metadata() {
+        let mut log = SentLog::new(THRESHOLD);
+        for id in 1..=20 {
+            log.record(id, id * 100).unwrap();
+        }
+        let received = (1..=20)
+            .filter(|&id| id != 5 && id != 12)
+            .collect::<Vec<_>>();
+        let (lost, acked) = decoded(log.on_quack(&receive(&received)));
+        assert_eq!(lost, vec![(5, 500), (12, 1200)]);
+        assert_eq!(ids(&acked), received);
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn test_losses_reported_once() {
+        let mut log = SentLog::new(THRESHOLD);
+        for id in 1..=10 {
+            log.record(id, ()).unwrap();
+        }
+        let mut received = (1..=10).filter(|&id| id != 3).collect::<Vec<_>>();
+        let (lost, _) = decoded(log.on_quack(&receive(&received)));
+        assert_eq!(ids(&lost), vec![3]);
+
+        // The receiver's quACK is cumulative and still missing packet 3.
+        for id in 11..=20 {
+            log.record(id, ()).unwrap();
+            if id != 17 {
+                received.push(id);
+            }
+        }
+        let (lost, acked) = decoded(log.on_quack(&receive(&received)));
+        assert_eq!(ids(&lost), vec![17]);
+        assert_eq!(acked.len(), 9);
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn test_in_flight_after_last_value() {
+        let mut log = SentLog::new(THRESHOLD);
+        for id in 1..=12 {
+            log.record(id, ()).unwrap();
+        }
+        let (lost, acked) = decoded(log.on_quack(&receive(&[1, 2, 3, 5, 6, 7, 8])));
+        assert_eq!(ids(&lost), vec![4]);
+        assert_eq!(ids(&acked), vec![1, 2, 3, 5, 6, 7, 8]);
+        assert_eq!(
+            ids(&log.iter().cloned().collect::<Vec<_>>()),
+            vec![9, 10, 11, 12]
+        );
+    }
+
+    #[test]
+    fn test_reordering_before_last_value() {
+        let mut log = SentLog::new(THRESHOLD);
+        for id in 1..=8 {
+            log.record(id, ()).unwrap();
+        }
+
+        // Packet 4 arrived after packets 5 and 6, so the last value is not the
+        // newest received packet. Nothing is lost.
+        let (lost, acked) = decoded(log.on_quack(&receive(&[1, 2, 3, 5, 6, 4])));
+        assert!(lost.is_empty());
+        assert_eq!(ids(&acked), vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(ids(&log.iter().cloned().collect::<Vec<_>>()), vec![7, 8]);
+
+        // Packet 7 was lost, and packet 8 arrived late.
+        let (lost, acked) = decoded(log.on_quack(&receive(&[1, 2, 3, 5, 6, 4, 8])));
+        assert_eq!(ids(&lost), vec![7]);
+        assert_eq!(ids(&acked), vec![8]);
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn test_many_in_flight_exceeds_threshold() {
+        const THRESHOLD: usize = 4;
+        let mut log = SentLog::new(THRESHOLD);
+        let mut received = PowerSumQuackU32::new(THRESHOLD);
+        for id in 1..=12 {
+            log.record(id, ()).unwrap();
+            if id <= 6 && id != 3 {
+                received.insert(id);
+            }
+        }
+
+        // Seven packets are missing, but only one was sent before the last
+        // packet received by the receiver.
+        let (lost, acked) = decoded(log.on_quack(&received));
+        assert_eq!(ids(&lost), vec![3]);
+        assert_eq!(ids(&acked), vec![1, 2, 4, 5, 6]);
+        assert_eq!(log.len(), 6);
+
+        // Too many losses before the last received packet.
+        for id in 13..=20 {
+            log.record(id, ()).unwrap();
+        }
+        received.insert(20);
+        let report = log.on_quack(&received);
+        assert_eq!(report, LossReport::ThresholdExceeded { num_missing: 13 });
+        assert_eq!(log.len(), 14);
+    }
+
+    #[test]
+    fn test_id_reuse_allow() {
+        let mut log = SentLog::new(THRESHOLD);
+        for (i, id) in [1, 2, 1, 3].into_iter().enumerate() {
+            log.record(id, i).unwrap();
+        }
+        let (lost, acked) = decoded(log.on_quack(&receive(&[1, 2, 3])));
+        assert_eq!(lost, vec![(1, 0)]);
+        assert_eq!(acked, vec![(2, 1), (1, 2), (3, 3)]);
+
+        for (i, id) in [1, 2, 1, 3].into_iter().enumerate() {
+            log.record(id, i + 4).unwrap();
+        }
+        let (lost, acked) = decoded(log.on_quack(&receive(&[1, 2, 3, 2, 3])));
+        assert_eq!(lost, vec![(1, 4), (1, 6)]);
+        assert_eq!(acked, vec![(2, 5), (3, 7)]);
+
+        // every copy was released
+        let mut log = log.id_reuse(IdReuse::Reject);
+        assert_eq!(log.record(1, 8), Ok(None));
+        assert_eq!(log.record(1, 9), Err(9));
+    }
+
+    #[test]
+    fn test_id_reuse_reject() {
+        let mut log = SentLog::new(THRESHOLD).id_reuse(IdReuse::Reject);
+        assert_eq!(log.record(1, "a"), Ok(None));
+        assert_eq!(log.record(1, "b"), Err("b"));
+        assert_eq!(log.len(), 1);
+
+        // The identifier can be reused once it is acknowledged.
+        decoded(log.on_quack(&receive(&[1])));
+        assert_eq!(log.record(1, "c"), Ok(None));
+
+        // or once it is evicted
+        let mut log = SentLog::with_capacity(THRESHOLD, 2).id_reuse(IdReuse::Reject);
+        assert_eq!(log.record(1, "a"), Ok(None));
+        assert_eq!(log.record(2, "b"), Ok(None));
+        assert_eq!(log.record(3, "c"), Ok(Some((1, "a"))));
+        assert_eq!(log.record(1, "d"), Ok(Some((2, "b"))));
+        assert_eq!(log.record(3, "e"), Err("e"));
+
+        // or once it is lost
+        let mut log = SentLog::new(THRESHOLD).id_reuse(IdReuse::Reject);
+        for (id, meta) in [(1, "a"), (2, "b")] {
+            assert_eq!(log.record(id, meta), Ok(None));
+        }
+        let (lost, _) = decoded(log.on_quack(&receive(&[2])));
+        assert_eq!(lost, vec![(1, "a")]);
+        assert_eq!(log.record(1, "c"), Ok(None));
+        assert_eq!(log.record(2, "d"), Ok(None));
+    }
+
+    #[test]
+    fn test_capacity_eviction() {
+        let mut log = SentLog::with_capacity(THRESHOLD, 3);
+        assert_eq!(log.capacity(), 3);
+        for id in 1..=3 {
+            assert_eq!(log.record(id, id), Ok(None));
+        }
+        assert_eq!(log.record(4, 4), Ok(Some((1, 1))));
+        assert_eq!(log.len(), 3);
+
+        // The evicted packet is assumed to be received.
+        let (lost, acked) = decoded(log.on_quack(&receive(&[1, 2, 4])));
+        assert_eq!(lost, vec![(3, 3)]);
+        assert_eq!(acked, vec![(2, 2), (4, 4)]);
+    }
+
+    #[test]
+    fn test_nothing_received() {
+        let mut log = SentLog::new(THRESHOLD);
+        for id in 1..=20 {
+            log.record(id, ()).unwrap();
+        }
+        let (lost, acked) = decoded(log.on_quack(&receive(&[])));
+        assert!(lost.is_empty());
+        assert!(acked.is_empty());
+        assert_eq!(log.len(), 20);
+    }
+
+    #[test]
+    fn test_threshold_mismatch() {
+        let mut log = SentLog::<()>::new(THRESHOLD);
+        let report = log.on_quack(&PowerSumQuackU32::new(THRESHOLD + 1));
+        assert_eq!(
+            report,
+            LossReport::ThresholdMismatch {
+                expected: THRESHOLD,
+                received: THRESHOLD + 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_retx_quack_before_tail_arrived() {
+        // The receiver sent its quACK after packet 6 arrived but before
+        // packets 7 to 10 arrived.
+        let log = (1..=10).collect::<Vec<u32>>();
+        let policy = RetxPolicy::default();
+        let classified = policy.classify(&log, &[7, 8, 9, 10], Some(6));
+        assert!(classified
+            .iter()
+            .all(|&(_, class)| class == Classification::TooRecent));
+        assert!(RetxPolicy::retransmissions(&classified).is_empty());
+    }
+
+    #[test]
+    fn test_retx_reorder_threshold() {
+        let log = (1..=10).collect::<Vec<u32>>();
+        let missing = [3, 5, 9];
+        let classified = RetxPolicy::new(3).classify(&log, &missing, Some(6));
+        assert_eq!(
+            classified,
+            vec![
+                (3, Classification::Lost),
+                (5, Classification::TooRecent),
+                (9, Classification::TooRecent),
+            ]
+        );
+        assert_eq!(RetxPolicy::retransmissions(&classified), vec![3]);
+
+        let classified = RetxPolicy::new(1).classify(&log, &missing, Some(6));
+        assert_eq!(RetxPolicy::retransmissions(&classified), vec![3, 5]);
+    }
+
+    #[test]
+    fn test_retx_unknown() {
+        let log = (1..=10).collect::<Vec<u32>>();
+        let classified = RetxPolicy::new(1).classify(&log, &[3, 42], Some(6));
+        assert_eq!(classified[1], (42, Classification::Unknown));
+        let classified = RetxPolicy::new(1).classify(&log, &[3], Some(42));
+        assert_eq!(classified, vec![(3, Classification::Unknown)]);
+        let classified = RetxPolicy::new(1).classify(&log, &[3], None);
+        assert_eq!(classified, vec![(3, Classification::Unknown)]);
+    }
+
+    #[test]
+    fn test_retx_reorder_delay() {
+        let start = Instant::now();
+        let log = (1..=10)
+            .map(|id| (id, start + Duration::from_millis(10 * id as u64)))
+            .collect::<Vec<_>>();
+        let now = start + Duration::from_millis(100);
+        let policy = RetxPolicy::new(3).reorder_delay(Duration::from_millis(45));
+
+        // Packet 5 was sent 50 ms ago, but packet 6 only 40 ms ago.
+        let classified = policy.classify_timed(&log, &[5, 6, 9], Some(7), now);
+        assert_eq!(
+            classified,
+            vec![
+                (5, Classification::Lost),
+                (6, Classification::TooRecent),
+                (9, Classification::TooRecent),
+            ]
+        );
+
+        // Without a delay, the timed classification is the same as untimed.
+        let classified = RetxPolicy::new(3).classify_timed(&log, &[5, 6, 9], Some(7), now);
+        assert!(RetxPolicy::retransmissions(&classified).is_empty());
+    }
+
+    #[test]
+    fn test_retx_with_sent_log() {
+        let mut sent = SentLog::new(THRESHOLD);
+        for id in 1..=10 {
+            sent.record(id, ()).unwrap();
+        }
+        let received = receive(&[1, 2, 4, 5, 6]);
+        let log = sent.iter().map(|&(id, _)| id).collect::<Vec<_>>();
+        let missing = receive(&log).sub(received.clone()).decode_with_log(&log);
+        let classified = RetxPolicy::default().classify(&log, &missing, received.last_value());
+        assert_eq!(RetxPolicy::retransmissions(&classified), vec![3]);
+    }
+}
@@ -0,0 +1,134 @@
+//! Builds a quACK from captured traffic.
+//!
+//! Captures packets with libpcap, derives an identifier from each IPv4
+//! packet with the [crate::id] module, and inserts it in a cumulative 32-bit
+//! power sum quACK. The quACK is emitted once per interval of capture time,
+//! measured by packet timestamps so offline captures behave the same as live
+//! ones, and once more when the capture ends.
+use crate::id::{self, SipKey};
+use crate::{PowerSumQuack, PowerSumQuackU32};
+use pcap::{Activated, Capture, Linktype};
+use std::path::Path;
+use std::time::Duration;
+
+/// How to derive the identifier of a captured IPv4 packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyStrategy {
+    /// [id::id_from_ipv4] of the IPv4 header.
+    Ipv4,
+    /// [id::id_from_payload_prefix] of the IPv4 payload, i.e., starting at the
+    /// transport header.
+    PayloadPrefix(SipKey),
+}
+
+/// Options for capturing packets.
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// A BPF filter applied to the capture, e.g., `"udp port 5103"`.
+    pub filter: Option<String>,
+    /// How often to emit the quACK, in capture time.
+    pub interval: Duration,
+    /// Stop after inserting this many packets in the quACK.
+    pub packet_limit: Option<usize>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            filter: None,
+            interval: Duration::from_millis(100),
+            packet_limit: None,
+        }
+    }
+}
+
+/// Captures packets on a live interface until the packet limit is reached,
+/// calling `emit` with the cumulative quACK after each interval.
+pub fn run(
+    interface: &str,
+    key_strategy: KeyStrategy,
+    threshold: usize,
+    options: &Options,
+    emit: impl FnMut(&PowerSumQuackU32),
+) -> Result<(), pcap::Error> {
+    let capture = Capture::from_device(interface)?
+        .immediate_mode(true)
+        .open()?;
+    sniff(capture, key_strategy, threshold, options, emit)
+}
+
+/// Reads packets from a pcap file until the end of the file or the packet
+/// limit is reached, calling `emit` with the cumulative quACK after each
+/// interval.
+pub fn run_offline(
+    path: impl AsRef<Path>,
+    key_strategy: KeyStrategy,
+    threshold: usize,
+    options: &Options,
+    emit: impl FnMut(&PowerSumQuackU32),
+) -> Result<(), pcap::Error> {
+    let capture = Capture::from_file(path)?;
+    sniff(capture, key_strategy, threshold, options, emit)
+}
+
+fn sniff<T: Activated + ?Sized>(
+    mut capture: Capture<T>,
+    key_strategy: KeyStrategy,
+    threshold: usize,
+    options: &Options,
+    mut emit: impl FnMut(&PowerSumQuackU32),
+) -> Result<(), pcap::Error> {
+    if let Some(filter) = &options.filter {
+        capture.filter(filter, true)?;
+    }
+    let linktype = capture.get_datalink();
+    let interval = options.interval.as_micros().max(1) as i64;
+    let mut quack = PowerSumQuackU32::new(threshold);
+    let mut deadline = None;
+    let mut num_packets = 0;
+    while options.packet_limit.is_none_or(|limit| num_packets < limit) {
+        let packet = match capture.next_packet() {
+            Ok(packet) => packet,
+            Err(pcap::Error::TimeoutExpired) => continue,
+            Err(pcap::Error::NoMorePackets) => break,
+            Err(e) => return Err(e),
+        };
+        // The timeval fields are narrower than i64 on some platforms.
+        #[allow(clippy::useless_conversion)]
+        let ts = i64::from(packet.header.ts.tv_sec) * 1_000_000 + i64::from(packet.header.ts.tv_usec);
+        let deadline = deadline.get_or_insert(ts + interval);
+        while ts >= *deadline {
+            emit(&quack);
+            *deadline += interval;
+        }
+        let Some(id) = ipv4_packet(linktype, packet.data).and_then(|ip| key(key_strategy, ip))
+        else {
+            continue;
+        };
+        quack.insert(id);
+        num_packets += 1;
+    }
+    emit(&quack);
+    Ok(())
+}
+
+/// The IPv4 packet in a captured frame, if any.
+fn ipv4_packet(linktype: Linktype, data: &[u8]) -> Option<&[u8]> {
+    let packet = match linktype {
+        Linktype::ETHERNET if data.get(12..14)? == [0x08, 0x00] => &data[14..],
+        Linktype::LINUX_SLL if data.get(14..16)? == [0x08, 0x00] => &data[16..],
+        Linktype::RAW | Linktype::IPV4 => data,
+        _ => return None,
+    };
+    (packet.first()? >> 4 == 4).then_some(packet)
+}
+
+fn key(key_strategy: KeyStrategy, packet: &[u8]) -> Option<u32> {
+    match key_strategy {
+        KeyStrategy::Ipv4 => id::id_from_ipv4(packet),
+        KeyStrategy::PayloadPrefix(key) => {
+            let header_len = usize::from(packet.first()? & 0x0f) * 4;
+            Some(id::id_from_payload_prefix(packet.get(header_len..)?, key))
+        }
+    }
+}
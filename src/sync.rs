@@ -0,0 +1,204 @@
+//! A power sum quACK that can be shared between threads or async tasks.
+use crate::{Error, PowerSumQuack, PowerSumQuackU32};
+use std::sync::{Arc, Mutex};
+
+/// A cumulative 32-bit power sum quACK behind an `Arc<Mutex<_>>`.
+///
+/// Cloning a `SharedQuack` clones the handle, not the quACK, so one task can
+/// insert elements while another periodically serializes snapshots of the
+/// same quACK. The lock is only held for the duration of each method call.
+#[derive(Clone, Debug)]
+pub struct SharedQuack {
+    inner: Arc<Mutex<PowerSumQuackU32>>,
+}
+
+impl SharedQuack {
+    /// Creates a new shared quACK that can decode at most `threshold` number
+    /// of elements.
+    pub fn new(threshold: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(PowerSumQuackU32::new(threshold))),
+        }
+    }
+
+    /// The maximum number of elements that can be decoded by the quACK.
+    pub fn threshold(&self) -> usize {
+        self.inner.lock().unwrap().threshold()
+    }
+
+    /// The number of elements represented by the quACK.
+    pub fn count(&self) -> u32 {
+        self.inner.lock().unwrap().count()
+    }
+
+    /// Insert an element in the quACK.
+    pub fn insert(&self, value: u32) {
+        self.inner.lock().unwrap().insert(value);
+    }
+
+    /// A copy of the quACK at this point in time.
+    pub fn snapshot(&self) -> PowerSumQuackU32 {
+        self.inner.lock().unwrap().clone()
+    }
+
+    /// A copy of the quACK at this point in time, serialized with `bincode`.
+    pub fn snapshot_serialize(&self) -> Vec<u8> {
+        let snapshot = self.snapshot();
+        bincode::serialize(&snapshot).unwrap()
+    }
+
+    /// Deserializes a quACK received from the other endpoint, subtracts it
+    /// from a snapshot of this quACK, and decodes the difference with the
+    /// elements in the log.
+    ///
+    /// This quACK is expected to represent a superset of the elements in the
+    /// received quACK, e.g., this is the sender and the received quACK comes
+    /// from the receiver. Returns the error from
+    /// [PowerSumQuackU32::deserialize_bounded] if the bytes cannot be
+    /// deserialized, [Error::ThresholdMismatch] if the received quACK has a
+    /// smaller threshold, or [Error::ExceedsThreshold] if the number of
+    /// elements in the difference exceeds the threshold.
+    pub fn sub_and_decode_from_bytes(&self, bytes: &[u8], log: &[u32]) -> Result<Vec<u32>, Error> {
+        let (received, _) = PowerSumQuackU32::deserialize_bounded(bytes, self.threshold())?;
+        let mut diff = self.snapshot();
+        diff.try_sub_assign(received)?;
+        if !diff.can_decode() {
+            return Err(Error::ExceedsThreshold {
+                count: diff.count(),
+                threshold: diff.threshold(),
+            });
+        }
+        Ok(diff.decode_with_log(log))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::thread;
+
+    const THRESHOLD: usize = 10;
+
+    #[test]
+    fn test_shared_quack_insert() {
+        let quack = SharedQuack::new(THRESHOLD);
+        let handle = quack.clone();
+        handle.insert(1);
+        handle.insert(2);
+        assert_eq!(quack.threshold(), THRESHOLD);
+        assert_eq!(quack.count(), 2);
+        assert_eq!(quack.snapshot().last_value(), Some(2));
+    }
+
+    #[test]
+    fn test_sub_and_decode_from_bytes() {
+        let sender = SharedQuack::new(THRESHOLD);
+        let receiver = SharedQuack::new(THRESHOLD);
+        let log = (1..=20).collect::<Vec<u32>>();
+        for &x in &log {
+            sender.insert(x);
+            if x % 5 != 0 {
+                receiver.insert(x);
+            }
+        }
+        let bytes = receiver.snapshot_serialize();
+        let mut missing = sender.sub_and_decode_from_bytes(&bytes, &log).unwrap();
+        missing.sort();
+        assert_eq!(missing, vec![5, 10, 15, 20]);
+    }
+
+    #[test]
+    fn test_sub_and_decode_from_bad_bytes() {
+        let sender = SharedQuack::new(THRESHOLD);
+        assert_eq!(
+            sender.sub_and_decode_from_bytes(&[1, 2, 3], &[]),
+            Err(Error::BufferTooSmall { needed: 8 })
+        );
+
+        // different thresholds
+        let bytes = SharedQuack::new(THRESHOLD - 1).snapshot_serialize();
+        assert_eq!(
+            sender.sub_and_decode_from_bytes(&bytes, &[]),
+            Err(Error::ThresholdMismatch {
+                expected: THRESHOLD,
+                actual: THRESHOLD - 1,
+            })
+        );
+        // a larger threshold is rejected before allocating the quACK
+        let bytes = SharedQuack::new(THRESHOLD + 1).snapshot_serialize();
+        assert!(matches!(
+            sender.sub_and_decode_from_bytes(&bytes, &[]),
+            Err(Error::MalformedBuffer(_))
+        ));
+
+        // too many missing elements
+        for x in 0..(THRESHOLD as u32 + 1) {
+            sender.insert(x);
+        }
+        let bytes = SharedQuack::new(THRESHOLD).snapshot_serialize();
+        assert_eq!(
+            sender.sub_and_decode_from_bytes(&bytes, &[]),
+            Err(Error::ExceedsThreshold {
+                count: THRESHOLD as u32 + 1,
+                threshold: THRESHOLD,
+            })
+        );
+    }
+
+    #[test]
+    fn test_concurrent_inserts_and_snapshots() {
+        const NUM_THREADS: u32 = 4;
+        const PER_THREAD: u32 = 250;
+        const NUM_ELEMS: u32 = NUM_THREADS * PER_THREAD;
+
+        // The sender knows about every element, but the receiver never sees
+        // the multiples of 200.
+        let log = (1..=NUM_ELEMS).collect::<Vec<u32>>();
+        let dropped = log
+            .iter()
+            .copied()
+            .filter(|x| x % 200 == 0)
+            .collect::<Vec<_>>();
+        let sender = SharedQuack::new(THRESHOLD);
+        for &x in &log {
+            sender.insert(x);
+        }
+
+        let receiver = SharedQuack::new(THRESHOLD);
+        let handles = (0..NUM_THREADS)
+            .map(|i| {
+                let receiver = receiver.clone();
+                thread::spawn(move || {
+                    for x in (i * PER_THREAD + 1)..=((i + 1) * PER_THREAD) {
+                        if x % 200 != 0 {
+                            receiver.insert(x);
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Snapshots taken while inserting are always internally consistent:
+        // either the difference is too large to decode, or the decoded
+        // elements account for exactly the elements not in the snapshot.
+        let mut last_count = 0;
+        while handles.iter().any(|handle| !handle.is_finished()) {
+            let bytes = receiver.snapshot_serialize();
+            let snapshot: PowerSumQuackU32 = bincode::deserialize(&bytes).unwrap();
+            assert!(snapshot.count() >= last_count);
+            last_count = snapshot.count();
+            if let Ok(missing) = sender.sub_and_decode_from_bytes(&bytes, &log) {
+                assert_eq!(missing.len() as u32, NUM_ELEMS - snapshot.count());
+                assert!(dropped.iter().all(|x| missing.contains(x)));
+            }
+        }
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let bytes = receiver.snapshot_serialize();
+        let mut missing = sender.sub_and_decode_from_bytes(&bytes, &log).unwrap();
+        missing.sort();
+        assert_eq!(missing, dropped);
+    }
+}
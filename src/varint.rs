@@ -0,0 +1,92 @@
+//! [LEB128](https://en.wikipedia.org/wiki/LEB128) encoding of unsigned
+//! integers: seven bits per byte, least significant first, with the high bit
+//! set on every byte but the last.
+use crate::Error;
+
+/// The maximum number of bytes in the encoding of a `u64`.
+pub(crate) const MAX_LEN: usize = 10;
+
+/// The number of bytes in the encoding of `n`.
+pub(crate) fn encoded_len(n: u64) -> usize {
+    let bits = 64 - n.leading_zeros() as usize;
+    bits.div_ceil(7).max(1)
+}
+
+/// Encodes `n` into the buffer at the offset, returning the offset after the
+/// encoding. The buffer must have at least [encoded_len] bytes after the
+/// offset.
+pub(crate) fn write(buf: &mut [u8], mut offset: usize, mut n: u64) -> usize {
+    while n >= 0x80 {
+        buf[offset] = (n as u8) | 0x80;
+        n >>= 7;
+        offset += 1;
+    }
+    buf[offset] = n as u8;
+    offset + 1
+}
+
+/// Decodes an integer from the bytes at the offset, returning the integer and
+/// the offset after the encoding.
+pub(crate) fn read(bytes: &[u8], offset: usize) -> Result<(u64, usize), Error> {
+    let mut n = 0u64;
+    for i in 0..MAX_LEN {
+        let byte = *bytes.get(offset + i).ok_or(Error::BufferTooSmall {
+            needed: offset + i + 1,
+        })?;
+        let bits = u64::from(byte & 0x7f);
+        if i == MAX_LEN - 1 && bits > 1 {
+            break;
+        }
+        n |= bits << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((n, offset + i + 1));
+        }
+    }
+    Err(Error::MalformedBuffer(format!(
+        "varint at offset {offset} overflows 64 bits"
+    )))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut buf = [0u8; MAX_LEN + 1];
+        for n in [0, 1, 0x7f, 0x80, 0x3fff, 0x4000, u32::MAX as u64, u64::MAX] {
+            let len = write(&mut buf, 1, n) - 1;
+            assert_eq!(len, encoded_len(n));
+            assert_eq!(read(&buf[..len + 1], 1).unwrap(), (n, len + 1));
+        }
+        assert_eq!(encoded_len(0), 1);
+        assert_eq!(encoded_len(u64::MAX), MAX_LEN);
+    }
+
+    #[test]
+    fn test_encoding() {
+        let mut buf = [0u8; 3];
+        assert_eq!(write(&mut buf, 0, 300), 2);
+        assert_eq!(&buf[..2], &[0xac, 0x02]);
+    }
+
+    #[test]
+    fn test_malformed() {
+        assert_eq!(
+            read(&[], 0).unwrap_err(),
+            Error::BufferTooSmall { needed: 1 }
+        );
+        assert_eq!(
+            read(&[0x80, 0x80], 0).unwrap_err(),
+            Error::BufferTooSmall { needed: 3 }
+        );
+        // the tenth byte may only hold the top bit of a u64
+        let mut bytes = [0xff; MAX_LEN];
+        bytes[MAX_LEN - 1] = 0x01;
+        assert_eq!(read(&bytes, 0).unwrap(), (u64::MAX, MAX_LEN));
+        bytes[MAX_LEN - 1] = 0x02;
+        assert!(matches!(read(&bytes, 0), Err(Error::MalformedBuffer(_))));
+        let bytes = [0x80; MAX_LEN + 1];
+        assert!(matches!(read(&bytes, 0), Err(Error::MalformedBuffer(_))));
+    }
+}
@@ -0,0 +1,336 @@
+//! A self-describing encoding of quACKs.
+//!
+//! The `bincode` serialization of a quACK does not say which quACK type it
+//! is, so a receiver that misreads it gets garbage instead of an error. This
+//! encoding prefixes the `bincode` serialization with a [HEADER_LEN]-byte
+//! header: the [MAGIC] bytes, the [WIRE_VERSION], the quACK kind, and the
+//! number of bits in each element. A receiver rejects a quACK with a future
+//! wire version with [Error::UnsupportedVersion] before reading the rest.
+//!
+//! [encode_with_checksum] also appends a CRC-32 of the encoding, so a
+//! receiver detects corrupted bytes with [Error::ChecksumMismatch] instead of
+//! decoding garbage power sums.
+use crate::negotiate::{Negotiable, QuackKind, WIRE_VERSION};
+use crate::{checksum, precompute, Error};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// The first bytes of an encoded quACK.
+pub const MAGIC: [u8; 2] = *b"QK";
+/// The number of bytes in the header.
+pub const HEADER_LEN: usize = 5;
+
+/// The header of an encoded quACK.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Header {
+    /// The version of the quACK wire format.
+    pub version: u8,
+    /// The quACK implementation.
+    pub kind: QuackKind,
+    /// The number of bits in each element.
+    pub bits: u8,
+}
+
+impl Header {
+    /// The header of a quACK of type `Q` at the current wire version.
+    pub fn of<Q: Negotiable>() -> Self {
+        Self {
+            version: WIRE_VERSION,
+            kind: Q::KIND,
+            bits: Q::BITS,
+        }
+    }
+
+    /// Encodes the header.
+    pub fn encode(&self) -> [u8; HEADER_LEN] {
+        let kind = match self.kind {
+            QuackKind::PowerSum => 0,
+            QuackKind::Montgomery => 1,
+            QuackKind::PowerTable => 2,
+        };
+        [MAGIC[0], MAGIC[1], self.version, kind, self.bits]
+    }
+
+    /// Decodes the header from the start of the buffer. Returns
+    /// [Error::UnsupportedVersion] if the wire version is not
+    /// [WIRE_VERSION], without reading the rest of the header.
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let header = bytes
+            .get(..HEADER_LEN)
+            .ok_or(Error::BufferTooSmall { needed: HEADER_LEN })?;
+        if header[..2] != MAGIC {
+            return Err(Error::MalformedBuffer(format!(
+                "invalid magic {:#04x} {:#04x}",
+                header[0], header[1]
+            )));
+        }
+        let version = header[2];
+        if version != WIRE_VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+        let kind = match header[3] {
+            0 => QuackKind::PowerSum,
+            1 => QuackKind::Montgomery,
+            2 => QuackKind::PowerTable,
+            kind => return Err(Error::MalformedBuffer(format!("unknown quACK kind {kind}"))),
+        };
+        Ok(Self {
+            version,
+            kind,
+            bits: header[4],
+        })
+    }
+}
+
+/// Encodes the quACK with its header.
+///
+/// # Examples
+///
+/// ```
+/// use quack::prelude::*;
+/// use quack::wire;
+///
+/// let bytes = wire::encode(&quack![1, 2, 3]);
+/// let quack: PowerSumQuackU32 = wire::decode(&bytes).unwrap();
+/// assert_eq!(quack.count(), 3);
+/// ```
+pub fn encode<Q: Negotiable + Serialize>(quack: &Q) -> Vec<u8> {
+    let mut bytes = Header::of::<Q>().encode().to_vec();
+    bincode::serialize_into(&mut bytes, quack).unwrap();
+    bytes
+}
+
+/// Decodes a quACK encoded with [encode]. Returns [Error::TypeMismatch] if
+/// the header is for a different quACK kind or element width than `Q`.
+///
+/// This is safe to call on untrusted input. Like
+/// [crate::PowerSumQuackU32::try_deserialize], the threshold must be at most
+/// the one set by [crate::global_config_set_max_power_sum_threshold], and the
+/// bytes must not be truncated or have trailing bytes.
+pub fn decode<Q: Negotiable + DeserializeOwned>(bytes: &[u8]) -> Result<Q, Error> {
+    let header = Header::decode(bytes)?;
+    if header.kind != Q::KIND || header.bits != Q::BITS {
+        return Err(Error::TypeMismatch {
+            kind: header.kind,
+            bits: header.bits,
+        });
+    }
+
+    // Every quACK type is serialized starting with the length of its power
+    // sums, so bound the threshold before deserializing them.
+    let mut body = &bytes[HEADER_LEN..];
+    let threshold = body.get(..8).ok_or(Error::BufferTooSmall {
+        needed: HEADER_LEN + 8,
+    })?;
+    let threshold = u64::from_le_bytes(threshold.try_into().unwrap());
+    let max_threshold = precompute::max_threshold();
+    if threshold == 0 {
+        return Err(Error::ZeroThreshold);
+    }
+    if threshold > max_threshold as u64 {
+        return Err(Error::MalformedBuffer(format!(
+            "threshold {threshold} exceeds the maximum {max_threshold}"
+        )));
+    }
+    let quack: Q =
+        bincode::deserialize_from(&mut body).map_err(|e| Error::MalformedBuffer(e.to_string()))?;
+    if !body.is_empty() {
+        return Err(Error::MalformedBuffer(format!(
+            "{} trailing bytes",
+            body.len()
+        )));
+    }
+    debug_assert_eq!(quack.threshold() as u64, threshold);
+    Ok(quack)
+}
+
+/// Encodes the quACK with its header like [encode], followed by a CRC-32 of
+/// the encoding.
+///
+/// # Examples
+///
+/// ```
+/// use quack::prelude::*;
+/// use quack::wire;
+///
+/// let mut bytes = wire::encode_with_checksum(&quack![1, 2, 3]);
+/// let quack: PowerSumQuackU32 = wire::decode_with_checksum(&bytes).unwrap();
+/// assert_eq!(quack.count(), 3);
+///
+/// bytes[20] ^= 1;
+/// let result = wire::decode_with_checksum::<PowerSumQuackU32>(&bytes);
+/// assert!(matches!(result, Err(Error::ChecksumMismatch { .. })));
+/// ```
+pub fn encode_with_checksum<Q: Negotiable + Serialize>(quack: &Q) -> Vec<u8> {
+    let mut bytes = encode(quack);
+    let len = bytes.len();
+    bytes.resize(len + checksum::LEN, 0);
+    checksum::write(&mut bytes, len);
+    bytes
+}
+
+/// Decodes a quACK encoded with [encode_with_checksum] like [decode].
+/// Returns [Error::ChecksumMismatch] if the bytes were corrupted, before
+/// reading the header.
+pub fn decode_with_checksum<Q: Negotiable + DeserializeOwned>(bytes: &[u8]) -> Result<Q, Error> {
+    let len = bytes
+        .len()
+        .checked_sub(checksum::LEN)
+        .ok_or(Error::BufferTooSmall {
+            needed: HEADER_LEN + checksum::LEN,
+        })?;
+    checksum::verify(bytes, len)?;
+    decode(&bytes[..len])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{quack, PowerSumQuack, PowerSumQuackU32};
+    use std::fmt::Debug;
+
+    const THRESHOLD: usize = 10;
+
+    fn check_round_trip<Q>(elems: &[Q::Element])
+    where
+        Q: Negotiable + Serialize + DeserializeOwned,
+        Q::Element: Copy + Debug + PartialEq,
+    {
+        let mut expected = Q::new(THRESHOLD);
+        elems.iter().for_each(|&x| expected.insert(x));
+        let bytes = encode(&expected);
+        assert_eq!(Header::decode(&bytes).unwrap(), Header::of::<Q>());
+        assert_eq!(&bytes[HEADER_LEN..], bincode::serialize(&expected).unwrap());
+        let actual: Q = decode(&bytes).unwrap();
+        assert_eq!(actual.count(), expected.count());
+        assert_eq!(actual.last_value(), expected.last_value());
+        assert_eq!(actual.decode_with_log(elems), elems.to_vec());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        check_round_trip::<PowerSumQuackU32>(&[7, 3, 11]);
+        #[cfg(feature = "montgomery")]
+        {
+            check_round_trip::<crate::PowerSumQuackU64>(&[7, 3, 1 << 40]);
+            check_round_trip::<crate::MontgomeryQuack>(&[7, 3, 1 << 40]);
+        }
+        #[cfg(feature = "power_table")]
+        {
+            crate::global_config_set_max_power_sum_threshold(THRESHOLD);
+            check_round_trip::<crate::PowerSumQuackU16>(&[7, 3, 11]);
+            check_round_trip::<crate::PowerTableQuack>(&[7, 3, 11]);
+        }
+    }
+
+    #[test]
+    fn test_header() {
+        let bytes = encode(&quack![1, 2, 3; threshold = THRESHOLD]);
+        assert_eq!(&bytes[..HEADER_LEN], &[b'Q', b'K', WIRE_VERSION, 0, 32]);
+        let header = Header {
+            version: WIRE_VERSION,
+            kind: QuackKind::PowerTable,
+            bits: 16,
+        };
+        assert_eq!(Header::decode(&header.encode()).unwrap(), header);
+    }
+
+    #[test]
+    fn test_checksum() {
+        let quack = quack![1, 2, 3; threshold = THRESHOLD];
+        let bytes = encode_with_checksum(&quack);
+        assert_eq!(&bytes[..bytes.len() - 4], &encode(&quack)[..]);
+        let actual: PowerSumQuackU32 = decode_with_checksum(&bytes).unwrap();
+        assert_eq!(actual.count(), 3);
+        assert!(decode::<PowerSumQuackU32>(&bytes).is_err());
+
+        // every flipped bit is detected
+        for i in 0..bytes.len() {
+            for bit in 0..8 {
+                let mut bad = bytes.clone();
+                bad[i] ^= 1 << bit;
+                assert!(matches!(
+                    decode_with_checksum::<PowerSumQuackU32>(&bad),
+                    Err(Error::ChecksumMismatch { .. })
+                ));
+            }
+        }
+        for len in 0..bytes.len() {
+            assert!(decode_with_checksum::<PowerSumQuackU32>(&bytes[..len]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_malformed() {
+        let bytes = encode(&quack![1, 2, 3; threshold = THRESHOLD]);
+        for len in 0..bytes.len() {
+            assert!(decode::<PowerSumQuackU32>(&bytes[..len]).is_err());
+        }
+        assert_eq!(
+            decode::<PowerSumQuackU32>(&bytes[..HEADER_LEN]).unwrap_err(),
+            Error::BufferTooSmall {
+                needed: HEADER_LEN + 8
+            }
+        );
+        let trailing = [&bytes[..], &[0]].concat();
+        assert_eq!(
+            decode::<PowerSumQuackU32>(&trailing).unwrap_err(),
+            Error::MalformedBuffer("1 trailing bytes".to_string())
+        );
+
+        let mut bad = bytes.clone();
+        bad[0] = 0;
+        assert!(matches!(
+            decode::<PowerSumQuackU32>(&bad),
+            Err(Error::MalformedBuffer(_))
+        ));
+
+        // a future version is rejected before the rest of the header
+        let mut bad = bytes.clone();
+        bad[2] = WIRE_VERSION + 1;
+        bad[3] = 0xff;
+        assert_eq!(
+            decode::<PowerSumQuackU32>(&bad).unwrap_err(),
+            Error::UnsupportedVersion(WIRE_VERSION + 1)
+        );
+
+        let mut bad = bytes.clone();
+        bad[3] = 3;
+        assert_eq!(
+            Header::decode(&bad).unwrap_err(),
+            Error::MalformedBuffer("unknown quACK kind 3".to_string())
+        );
+
+        let mut bad = bytes.clone();
+        bad[4] = 64;
+        assert_eq!(
+            decode::<PowerSumQuackU32>(&bad).unwrap_err(),
+            Error::TypeMismatch {
+                kind: QuackKind::PowerSum,
+                bits: 64
+            }
+        );
+
+        // a power sum outside the field would overflow when decoding
+        let mut bad = bytes.clone();
+        bad[HEADER_LEN + 8..HEADER_LEN + 12].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            decode::<PowerSumQuackU32>(&bad),
+            Err(Error::MalformedBuffer(_))
+        ));
+
+        // a huge threshold is rejected before allocating
+        let mut bad = bytes.clone();
+        bad[HEADER_LEN..HEADER_LEN + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            decode::<PowerSumQuackU32>(&bad),
+            Err(Error::MalformedBuffer(_))
+        ));
+        let mut bad = bytes;
+        bad[HEADER_LEN..HEADER_LEN + 8].copy_from_slice(&0u64.to_le_bytes());
+        assert_eq!(
+            decode::<PowerSumQuackU32>(&bad).unwrap_err(),
+            Error::ZeroThreshold
+        );
+    }
+}
@@ -0,0 +1,341 @@
+//! Runs every wire decoder over a table of adversarial byte patterns. The
+//! decoders must return an error or a value without panicking, allocating
+//! more than the bounded threshold allows, or reading out of bounds. The
+//! `fuzz/` directory has `cargo-fuzz` targets that explore the same decoders
+//! more thoroughly.
+
+use quack::frame::QuackFrame;
+use quack::id::{id_from_ipv4, id_from_payload_prefix, id_from_quic_dcid_and_pn, SipKey};
+use quack::multipath::QuackSet;
+use quack::negotiate::{IdStrategy, Message, Negotiable};
+use quack::sender::SentLog;
+use quack::sync::SharedQuack;
+use quack::wire;
+use quack::{PowerSumQuack, PowerSumQuackU32};
+
+const THRESHOLD: usize = 10;
+
+fn quack() -> PowerSumQuackU32 {
+    let mut quack = PowerSumQuackU32::new(THRESHOLD);
+    for x in 1..=5 {
+        quack.insert(x * 1000);
+    }
+    quack
+}
+
+/// Valid encodings of each wire format, used as the base for truncations and
+/// byte flips.
+fn valid_encodings() -> Vec<Vec<u8>> {
+    let mut set = QuackSet::new(THRESHOLD);
+    (0..6).for_each(|x| set.insert((x % 2) as u8, x * 1000));
+    let offer = Message::Offer {
+        kinds: vec![PowerSumQuackU32::params(20, IdStrategy::Ipv4)],
+    };
+    vec![
+        bincode::serialize(&quack()).unwrap(),
+        bincode::serialize(&PowerSumQuackU32::new(THRESHOLD)).unwrap(),
+        QuackFrame {
+            seq: 7,
+            path_id: Some(1),
+            quack: quack(),
+        }
+        .encode(),
+        set.serialize(),
+        offer.serialize(),
+        wire::encode(&quack()),
+        wire::encode_with_checksum(&quack()),
+        compact(&quack()),
+        delta(&PowerSumQuackU32::new(THRESHOLD), &quack()),
+    ]
+}
+
+fn compact(quack: &PowerSumQuackU32) -> Vec<u8> {
+    let mut buf = vec![0; quack.serialized_compact_len()];
+    quack.serialize_compact(&mut buf).unwrap();
+    buf
+}
+
+fn delta(prev: &PowerSumQuackU32, next: &PowerSumQuackU32) -> Vec<u8> {
+    let mut buf = vec![0; next.serialized_delta_len(prev)];
+    next.serialize_delta(prev, &mut buf).unwrap();
+    buf
+}
+
+/// The CRC-32 (IEEE 802.3) of the bytes, to checksum modified encodings.
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |mut crc, &byte| {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg());
+        }
+        crc
+    })
+}
+
+fn with_checksum(bytes: &[u8]) -> Vec<u8> {
+    [bytes, &crc32(bytes).to_le_bytes()].concat()
+}
+
+/// Serialized quACKs with the first power sum or the last value set to a
+/// value that is not less than the modulus.
+fn out_of_field_quacks() -> Vec<Vec<u8>> {
+    let bytes = bincode::serialize(&quack()).unwrap();
+    [8, 8 + 4 * THRESHOLD + 1]
+        .into_iter()
+        .map(|offset| {
+            let mut bytes = bytes.clone();
+            bytes[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+            bytes
+        })
+        .collect()
+}
+
+/// The serialized quACK in a frame, a quACK set, and a wire encoding.
+fn framed(quack: &[u8]) -> [Vec<u8>; 3] {
+    let len = (4 + 1 + quack.len()) as u16;
+    let mut frame = vec![0x51, 0x01];
+    frame.extend_from_slice(&len.to_be_bytes());
+    frame.extend_from_slice(&[0, 0, 0, 7, 1]);
+    frame.extend_from_slice(quack);
+    let header = &wire::encode(&PowerSumQuackU32::new(1))[..wire::HEADER_LEN];
+    [
+        frame,
+        [&[1, 0, 0][..], quack].concat(),
+        [header, quack].concat(),
+    ]
+}
+
+/// Byte patterns that are not valid encodings of anything, or are valid
+/// encodings that claim to be very large.
+fn adversarial_inputs() -> Vec<Vec<u8>> {
+    let mut inputs = vec![vec![], vec![0], vec![0xff]];
+    for len in [4, 8, 13, 64, 1500] {
+        inputs.push(vec![0; len]);
+        inputs.push(vec![0xff; len]);
+    }
+    for threshold in [0, 1, THRESHOLD as u64 + 1, 1 << 32, 1 << 60, u64::MAX] {
+        let mut bytes = threshold.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&[0; 64]);
+        inputs.push(bytes.clone());
+        // the same length prefix behind a frame header, a path count, and an
+        // offer tag
+        let mut frame = vec![0x51, 0, 0, 76, 0, 0, 0, 1];
+        frame.extend_from_slice(&bytes);
+        inputs.push(frame);
+        inputs.push([&[1, 0, 0][..], &bytes].concat());
+        inputs.push([&[0, 0, 0, 0][..], &bytes].concat());
+    }
+    for bytes in out_of_field_quacks() {
+        let [frame, set, wire] = framed(&bytes);
+        inputs.push(with_checksum(&bytes));
+        inputs.push(with_checksum(&wire));
+        inputs.extend([bytes, frame, set, wire]);
+    }
+    for bytes in valid_encodings() {
+        for len in 0..bytes.len() {
+            inputs.push(bytes[..len].to_vec());
+        }
+        for i in 0..bytes.len() {
+            for flip in [0x01, 0x80, 0xff] {
+                let mut bytes = bytes.clone();
+                bytes[i] ^= flip;
+                inputs.push(bytes);
+            }
+        }
+        inputs.push([&bytes[..], &[0]].concat());
+        inputs.push(bytes);
+    }
+    inputs
+}
+
+/// Decoding a quACK from untrusted bytes must not panic.
+fn check_decode(quack: &PowerSumQuackU32) {
+    let log = (1..=20).map(|x| x * 1000).collect::<Vec<_>>();
+    let _ = quack.try_decode_with_log(&log);
+    let _ = quack.decode_with_log_detailed(&log);
+}
+
+#[test]
+fn test_power_sum_decoders() {
+    let mut reused = PowerSumQuackU32::new(THRESHOLD);
+    for bytes in adversarial_inputs() {
+        if let Ok((quack, len)) = PowerSumQuackU32::deserialize_bounded(&bytes, THRESHOLD) {
+            assert!(quack.threshold() <= THRESHOLD);
+            assert!(len <= bytes.len());
+            check_decode(&quack);
+        }
+        if let Ok((quack, len)) = PowerSumQuackU32::deserialize_compact(&bytes, THRESHOLD) {
+            assert!(quack.threshold() <= THRESHOLD);
+            assert!(len <= bytes.len());
+            check_decode(&quack);
+        }
+        if let Ok((quack, len)) = PowerSumQuackU32::deserialize_with_checksum(&bytes, THRESHOLD) {
+            assert!(len <= bytes.len());
+            check_decode(&quack);
+        }
+        let before = bincode::serialize(&reused).unwrap();
+        let mut delta = reused.clone();
+        match delta.apply_delta(&bytes) {
+            Ok(len) => assert!(len <= bytes.len()),
+            Err(_) => assert_eq!(bincode::serialize(&delta).unwrap(), before),
+        }
+        match reused.deserialize_into(&bytes) {
+            Ok(len) => {
+                assert!(len <= bytes.len());
+                check_decode(&reused);
+            }
+            Err(_) => assert_eq!(bincode::serialize(&reused).unwrap(), before),
+        }
+    }
+}
+
+#[test]
+fn test_frame_decoder() {
+    for bytes in adversarial_inputs() {
+        if let Ok((frame, len)) = QuackFrame::decode(&bytes) {
+            assert!(len <= bytes.len());
+            check_decode(&frame.quack);
+
+            // the sender interprets the quACK in the frame
+            let mut log = SentLog::new(THRESHOLD);
+            for x in 1..=20 {
+                log.record(x * 1000, ()).unwrap();
+            }
+            log.on_quack(&frame.quack);
+        }
+    }
+}
+
+#[test]
+fn test_multipath_decoder() {
+    for bytes in adversarial_inputs() {
+        if let Ok(set) = QuackSet::deserialize(&bytes, THRESHOLD) {
+            for path_id in set.paths().collect::<Vec<_>>() {
+                check_decode(set.get(path_id).unwrap());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_negotiate_decoder() {
+    for bytes in adversarial_inputs() {
+        match Message::deserialize(&bytes) {
+            Ok(Message::Offer { kinds }) => kinds.iter().for_each(|params| {
+                let _ = params.try_build::<PowerSumQuackU32>();
+            }),
+            Ok(Message::Accept { chosen }) => {
+                let _ = chosen.try_build::<PowerSumQuackU32>();
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+#[test]
+fn test_wire_decoder() {
+    for bytes in adversarial_inputs() {
+        if let Ok(quack) = wire::decode::<PowerSumQuackU32>(&bytes) {
+            assert!(quack.threshold() <= quack::builder::DEFAULT_THRESHOLD);
+            check_decode(&quack);
+        }
+        if let Ok(quack) = wire::decode_with_checksum::<PowerSumQuackU32>(&bytes) {
+            check_decode(&quack);
+        }
+        let _ = wire::Header::decode(&bytes);
+    }
+}
+
+#[test]
+fn test_sync_decoder() {
+    let sync = SharedQuack::new(THRESHOLD);
+    (1..=5).for_each(|x| sync.insert(x * 1000));
+    let log = (1..=20).map(|x| x * 1000).collect::<Vec<_>>();
+    for bytes in adversarial_inputs() {
+        let _ = sync.sub_and_decode_from_bytes(&bytes, &log);
+    }
+}
+
+/// Every decoder rejects power sums and last values outside the field, like
+/// `bincode::deserialize` does.
+#[test]
+fn test_out_of_field_rejected() {
+    let sync = SharedQuack::new(THRESHOLD);
+    (1..=5).for_each(|x| sync.insert(x * 1000));
+    for bytes in out_of_field_quacks() {
+        let [frame, set, wire] = framed(&bytes);
+        assert!(bincode::deserialize::<PowerSumQuackU32>(&bytes).is_err());
+        assert!(PowerSumQuackU32::deserialize_bounded(&bytes, THRESHOLD).is_err());
+        assert!(PowerSumQuackU32::try_deserialize(&bytes).is_err());
+        assert!(PowerSumQuackU32::new(THRESHOLD)
+            .deserialize_into(&bytes)
+            .is_err());
+        assert!(
+            PowerSumQuackU32::deserialize_with_checksum(&with_checksum(&bytes), THRESHOLD).is_err()
+        );
+        assert!(sync.sub_and_decode_from_bytes(&bytes, &[]).is_err());
+        assert!(QuackFrame::decode(&frame).is_err());
+        assert!(QuackSet::deserialize(&set, THRESHOLD).is_err());
+        assert!(wire::decode::<PowerSumQuackU32>(&wire).is_err());
+        assert!(wire::decode_with_checksum::<PowerSumQuackU32>(&with_checksum(&wire)).is_err());
+
+        // the same bytes with the value in the field decode
+        let mut valid = bytes.clone();
+        let offset = valid.windows(4).position(|w| w == [0xff; 4]).unwrap();
+        valid[offset..offset + 4].copy_from_slice(&0u32.to_le_bytes());
+        let [frame, set, wire] = framed(&valid);
+        assert!(
+            PowerSumQuackU32::deserialize_with_checksum(&with_checksum(&valid), THRESHOLD).is_ok()
+        );
+        assert!(QuackFrame::decode(&frame).is_ok());
+        assert!(QuackSet::deserialize(&set, THRESHOLD).is_ok());
+        assert!(wire::decode_with_checksum::<PowerSumQuackU32>(&with_checksum(&wire)).is_ok());
+    }
+}
+
+#[test]
+fn test_id_parsers() {
+    let key = SipKey::default();
+    for bytes in adversarial_inputs() {
+        let _ = id_from_ipv4(&bytes);
+        let _ = id_from_payload_prefix(&bytes, key);
+        let _ = id_from_quic_dcid_and_pn(&bytes, u64::MAX, key);
+    }
+}
+
+/// The seeds in the fuzz corpus are valid encodings.
+#[test]
+fn test_fuzz_seeds() {
+    let seeds = |target: &str| {
+        let dir = format!("{}/fuzz/corpus/{}", env!("CARGO_MANIFEST_DIR"), target);
+        // only the seeds are checked in, next to inputs found by fuzzing
+        std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.file_name()
+                    .unwrap()
+                    .to_str()
+                    .unwrap()
+                    .starts_with("seed-")
+            })
+            .map(|path| std::fs::read(path).unwrap())
+            .collect::<Vec<_>>()
+    };
+    for bytes in seeds("power_sum") {
+        let (_, len) = PowerSumQuackU32::deserialize_bounded(&bytes, 20).unwrap();
+        assert_eq!(len, bytes.len());
+    }
+    for bytes in seeds("frame") {
+        assert!(QuackFrame::decode(&bytes).is_ok());
+    }
+    for bytes in seeds("negotiate") {
+        assert!(Message::deserialize(&bytes).is_ok());
+    }
+    for bytes in seeds("multipath") {
+        assert!(QuackSet::deserialize(&bytes, 20).is_ok());
+    }
+    for bytes in seeds("wire") {
+        assert!(wire::decode::<PowerSumQuackU32>(&bytes).is_ok());
+    }
+}
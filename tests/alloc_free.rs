@@ -0,0 +1,139 @@
+//! Checks that the allocation-free APIs do not allocate, with an allocator
+//! that counts the allocations made by the current thread.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use quack::frame::QuackFrame;
+use quack::prelude::*;
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// The number of allocations made by the closure on this thread.
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(|n| n.get());
+    f();
+    ALLOCATIONS.with(|n| n.get()) - before
+}
+
+const THRESHOLD: usize = 20;
+
+fn quack_of(ids: impl IntoIterator<Item = u32>) -> PowerSumQuackU32 {
+    let mut quack = PowerSumQuackU32::new(THRESHOLD);
+    ids.into_iter().for_each(|id| quack.insert(id));
+    quack
+}
+
+#[test]
+fn test_counter_sees_allocations() {
+    assert!(allocations(|| drop(vec![0u8; 16])) > 0);
+}
+
+#[test]
+fn test_insert_remove_sub_assign() {
+    let mut quack = quack_of(0..10);
+    let other = quack_of(0..5);
+    let batch = (1000..1020).collect::<Vec<_>>();
+    let n = allocations(|| {
+        quack.insert_batch(&batch);
+        quack.extend(&batch);
+        quack.insert(100);
+        quack.remove(100);
+        quack.sub_assign(other);
+    });
+    assert_eq!(n, 0);
+}
+
+#[test]
+fn test_to_coeffs_preallocated() {
+    let quack = quack_of(0..10);
+    let mut coeffs = quack.to_coeffs();
+    assert_eq!(allocations(|| quack.to_coeffs_preallocated(&mut coeffs)), 0);
+}
+
+#[test]
+fn test_serialize_and_deserialize_into() {
+    let sent = quack_of(0..10);
+    let mut received = PowerSumQuackU32::new(THRESHOLD);
+    let mut buf = [0u8; 256];
+    let n = allocations(|| {
+        let len = sent.serialize_into(&mut buf).unwrap();
+        received.deserialize_into(&buf[..len]).unwrap();
+    });
+    assert_eq!(n, 0);
+    assert_eq!(received.count(), 10);
+}
+
+#[test]
+fn test_serialize_compact() {
+    let quack = quack_of(0..10);
+    let mut buf = [0u8; 256];
+    let n = allocations(|| {
+        quack.serialized_compact_len();
+        quack.serialize_compact(&mut buf).unwrap();
+    });
+    assert_eq!(n, 0);
+}
+
+#[test]
+fn test_serialize_and_apply_delta() {
+    let prev = quack_of(0..10);
+    let next = quack_of(0..11);
+    let mut quack = prev.clone();
+    let mut buf = [0u8; 256];
+    let n = allocations(|| {
+        let len = next.serialize_delta(&prev, &mut buf).unwrap();
+        quack.apply_delta(&buf[..len]).unwrap();
+    });
+    assert_eq!(n, 0);
+    assert_eq!(quack.count(), 11);
+}
+
+#[test]
+fn test_serialize_with_checksum() {
+    let quack = quack_of(0..10);
+    let mut buf = [0u8; 256];
+    let n = allocations(|| {
+        quack.serialize_with_checksum(&mut buf).unwrap();
+    });
+    assert_eq!(n, 0);
+}
+
+#[test]
+fn test_frame_encode_into() {
+    let frame = QuackFrame {
+        seq: 1,
+        path_id: Some(2),
+        quack: quack_of(0..10),
+    };
+    let mut buf = [0u8; 256];
+    let mut len = 0;
+    assert_eq!(
+        allocations(|| len = frame.encode_into(&mut buf).unwrap()),
+        0
+    );
+    assert_eq!(len, frame.encoded_len());
+}
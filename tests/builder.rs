@@ -0,0 +1,34 @@
+//! Checks that [QuackBuilder] raises the maximum threshold of the precomputed
+//! tables before they are computed. This is in its own test binary so no other
+//! test computes the tables first.
+
+use quack::prelude::*;
+
+#[test]
+fn test_builder_raises_max_threshold() {
+    const THRESHOLD: usize = 50;
+
+    // without the builder, decoding more than the default maximum threshold
+    // of missing elements would be out of range
+    let elems = (1..=100).collect::<Vec<u32>>();
+    let builder = QuackBuilder::power_sum().threshold(THRESHOLD);
+    let mut sent = builder.build().unwrap();
+    let mut received = builder.build().unwrap();
+    elems.iter().for_each(|&x| sent.insert(x));
+    elems[50..].iter().for_each(|&x| received.insert(x));
+    sent.sub_assign(received);
+    let mut missing = sent.decode_with_log(&elems);
+    missing.sort();
+    assert_eq!(missing, elems[..50]);
+
+    // the tables are computed, so a larger threshold is too large
+    let builder = QuackBuilder::power_sum().threshold(THRESHOLD + 1);
+    assert_eq!(
+        builder.build().unwrap_err(),
+        Error::ThresholdTooLarge {
+            threshold: THRESHOLD + 1,
+            max: THRESHOLD
+        }
+    );
+    assert!(QuackBuilder::power_sum().threshold(10).build().is_ok());
+}
@@ -0,0 +1,109 @@
+//! Checks that each optional feature works on its own and together with the
+//! others. Run with each feature alone and with all features enabled, e.g.,
+//! `make feature-matrix`.
+
+use std::fmt::Debug;
+
+use quack::{PowerSumQuack, PowerSumQuackU32};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+const THRESHOLD: usize = 10;
+
+/// Sends the elements, drops the first two, and decodes a quACK of the
+/// received elements after a round trip through `bincode`.
+fn check_round_trip<Q>(elems: &[Q::Element])
+where
+    Q: PowerSumQuack + Serialize + DeserializeOwned,
+    Q::Element: Copy + Ord + Debug,
+{
+    let mut sent = Q::new(THRESHOLD);
+    let mut received = Q::new(THRESHOLD);
+    for (i, &x) in elems.iter().enumerate() {
+        sent.insert(x);
+        if i >= 2 {
+            received.insert(x);
+        }
+    }
+    let bytes = bincode::serialize(&received).unwrap();
+    let received: Q = bincode::deserialize(&bytes).unwrap();
+    sent.sub_assign(received);
+    let mut missing = sent.decode_with_log(elems);
+    missing.sort();
+    let mut expected = elems[..2].to_vec();
+    expected.sort();
+    assert_eq!(missing, expected);
+}
+
+#[test]
+fn test_power_sum_u32() {
+    check_round_trip::<PowerSumQuackU32>(&[7, 3, 11, 13, 17]);
+}
+
+#[cfg(feature = "montgomery")]
+#[test]
+fn test_montgomery() {
+    check_round_trip::<quack::PowerSumQuackU64>(&[7, 3, 11, 13, 1 << 40]);
+    check_round_trip::<quack::MontgomeryQuack>(&[7, 3, 11, 13, 1 << 40]);
+}
+
+#[cfg(feature = "power_table")]
+#[test]
+fn test_power_table() {
+    quack::global_config_set_max_power_sum_threshold(THRESHOLD);
+    check_round_trip::<quack::PowerSumQuackU16>(&[7, 3, 11, 13, 17]);
+    check_round_trip::<quack::PowerTableQuack>(&[7, 3, 11, 13, 17]);
+}
+
+#[cfg(feature = "strawmen")]
+#[test]
+fn test_strawmen() {
+    let a = quack::StrawmanAQuack { sidekick_id: 5 };
+    let bytes = bincode::serialize(&a).unwrap();
+    let a: quack::StrawmanAQuack = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(a.sidekick_id, 5);
+
+    let mut b = quack::StrawmanBQuack::new(3);
+    (1..=4).for_each(|x| b.insert(x));
+    let bytes = bincode::serialize(&b).unwrap();
+    let b: quack::StrawmanBQuack = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(b.window, [3, 4]);
+}
+
+#[cfg(feature = "libpari")]
+#[test]
+fn test_libpari() {
+    let mut quack = PowerSumQuackU32::new(THRESHOLD);
+    quack.insert(7);
+    quack.insert(3);
+    let mut roots = quack.decode_by_factorization().unwrap();
+    roots.sort();
+    assert_eq!(roots, vec![3, 7]);
+}
+
+#[cfg(feature = "sniffer")]
+#[test]
+fn test_sniffer() {
+    let options = quack::sniffer::Options::default();
+    assert!(options.filter.is_none());
+}
+
+#[cfg(all(feature = "montgomery", feature = "power_table"))]
+#[test]
+fn test_negotiate_all_kinds() {
+    use quack::negotiate::{resolve, IdStrategy, Negotiable};
+
+    let supported = [
+        quack::PowerTableQuack::params(THRESHOLD as u16, IdStrategy::Ipv4),
+        quack::MontgomeryQuack::params(THRESHOLD as u16, IdStrategy::Ipv4),
+        quack::PowerSumQuackU16::params(THRESHOLD as u16, IdStrategy::Ipv4),
+        quack::PowerSumQuackU64::params(THRESHOLD as u16, IdStrategy::Ipv4),
+        PowerSumQuackU32::params(THRESHOLD as u16, IdStrategy::Ipv4),
+    ];
+    for offer in supported {
+        assert_eq!(resolve(&[offer], &supported), Some(offer));
+    }
+    let chosen = resolve(&supported[1..], &supported).unwrap();
+    assert!(chosen.build::<quack::MontgomeryQuack>().is_some());
+    assert!(chosen.build::<quack::PowerSumQuackU64>().is_none());
+}
@@ -0,0 +1,165 @@
+#![cfg(feature = "sniffer")]
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use quack::id::{self, SipKey};
+use quack::sniffer::{self, KeyStrategy, Options};
+use quack::{PowerSumQuack, PowerSumQuackU32};
+
+const THRESHOLD: usize = 10;
+const LINKTYPE_ETHERNET: u32 = 1;
+const ETHERTYPE_IPV4: [u8; 2] = [0x08, 0x00];
+const ETHERTYPE_ARP: [u8; 2] = [0x08, 0x06];
+const UDP: u8 = 17;
+const TCP: u8 = 6;
+
+/// An IPv4 packet with an 8-byte transport header and 4-byte payload.
+fn ipv4_packet(ident: u16, protocol: u8) -> Vec<u8> {
+    let mut packet = vec![0x45, 0x00, 0x00, 32];
+    packet.extend_from_slice(&ident.to_be_bytes());
+    packet.extend_from_slice(&[0x40, 0x00, 0x40, protocol, 0x00, 0x00]);
+    packet.extend_from_slice(&[10, 0, 0, 1, 10, 0, 0, 2]);
+    packet.extend_from_slice(&[0x13, 0xef, 0x13, 0xef, 0x00, 0x0c, 0x00, 0x00]);
+    packet.extend_from_slice(&ident.to_be_bytes().repeat(2));
+    packet
+}
+
+fn ethernet_frame(ethertype: [u8; 2], payload: &[u8]) -> Vec<u8> {
+    let mut frame = vec![0x02, 0, 0, 0, 0, 2, 0x02, 0, 0, 0, 0, 1];
+    frame.extend_from_slice(&ethertype);
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// Writes a pcap file with the frames at the given timestamps in
+/// microseconds, returning its path.
+fn write_pcap(name: &str, frames: &[(u64, Vec<u8>)]) -> PathBuf {
+    let mut bytes = vec![];
+    for field in [0xa1b2c3d4u32, 0x0004_0002, 0, 0, 65535, LINKTYPE_ETHERNET] {
+        bytes.extend_from_slice(&field.to_le_bytes());
+    }
+    // the version fields are two u16s, not one u32
+    bytes[4..8].copy_from_slice(&[2, 0, 4, 0]);
+    for (ts, frame) in frames {
+        let len = frame.len() as u32;
+        for field in [(ts / 1_000_000) as u32, (ts % 1_000_000) as u32, len, len] {
+            bytes.extend_from_slice(&field.to_le_bytes());
+        }
+        bytes.extend_from_slice(frame);
+    }
+    let path = std::env::temp_dir().join(format!("quack-{}-{}.pcap", name, std::process::id()));
+    fs::write(&path, bytes).unwrap();
+    path
+}
+
+/// Twelve UDP packets every 10 ms, with an ARP frame and a TCP packet mixed in.
+fn capture(name: &str) -> (PathBuf, Vec<Vec<u8>>) {
+    let mut frames = vec![];
+    let mut udp = vec![];
+    for i in 0..12u16 {
+        let packet = ipv4_packet(1000 + i, UDP);
+        frames.push((
+            u64::from(i) * 10_000,
+            ethernet_frame(ETHERTYPE_IPV4, &packet),
+        ));
+        udp.push(packet);
+        if i == 3 {
+            frames.push((35_000, ethernet_frame(ETHERTYPE_ARP, &[0; 28])));
+            let tcp = ipv4_packet(2000, TCP);
+            frames.push((36_000, ethernet_frame(ETHERTYPE_IPV4, &tcp)));
+        }
+    }
+    (write_pcap(name, &frames), udp)
+}
+
+fn run(path: &PathBuf, key_strategy: KeyStrategy, options: &Options) -> Vec<PowerSumQuackU32> {
+    let mut quacks = vec![];
+    sniffer::run_offline(path, key_strategy, THRESHOLD, options, |quack| {
+        quacks.push(quack.clone())
+    })
+    .unwrap();
+    quacks
+}
+
+fn quack_of(ids: impl IntoIterator<Item = u32>) -> PowerSumQuackU32 {
+    let mut quack = PowerSumQuackU32::new(THRESHOLD);
+    ids.into_iter().for_each(|id| quack.insert(id));
+    quack
+}
+
+fn assert_same(actual: &PowerSumQuackU32, expected: &PowerSumQuackU32) {
+    let actual = bincode::serialize(actual).unwrap();
+    assert_eq!(actual, bincode::serialize(expected).unwrap());
+}
+
+#[test]
+fn test_per_interval_counts_and_ids() {
+    let (path, udp) = capture("intervals");
+    let options = Options {
+        filter: Some("udp".to_string()),
+        interval: Duration::from_millis(50),
+        packet_limit: None,
+    };
+    let quacks = run(&path, KeyStrategy::Ipv4, &options);
+    let counts = quacks.iter().map(|q| q.count()).collect::<Vec<_>>();
+    assert_eq!(counts, vec![5, 10, 12]);
+
+    let ids = udp
+        .iter()
+        .map(|p| id::id_from_ipv4(p).unwrap())
+        .collect::<Vec<_>>();
+    assert_same(&quacks[2], &quack_of(ids.clone()));
+
+    // the first interval holds exactly the first five packets
+    assert_same(&quacks[0], &quack_of(ids[..5].to_vec()));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_unfiltered_skips_non_ipv4() {
+    let (path, _) = capture("unfiltered");
+    let quacks = run(&path, KeyStrategy::Ipv4, &Options::default());
+    assert_eq!(quacks.last().unwrap().count(), 13);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_packet_limit_and_filter() {
+    let (path, _) = capture("limit");
+    let options = Options {
+        filter: Some("udp".to_string()),
+        interval: Duration::from_millis(50),
+        packet_limit: Some(7),
+    };
+    let counts = run(&path, KeyStrategy::Ipv4, &options)
+        .iter()
+        .map(|q| q.count())
+        .collect::<Vec<_>>();
+    assert_eq!(counts, vec![5, 7]);
+
+    let options = Options {
+        filter: Some("tcp".to_string()),
+        ..Options::default()
+    };
+    let quacks = run(&path, KeyStrategy::Ipv4, &options);
+    assert_eq!(quacks.last().unwrap().count(), 1);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_payload_prefix_ids() {
+    let (path, udp) = capture("payload");
+    let key = SipKey([7; 16]);
+    let options = Options {
+        filter: Some("udp".to_string()),
+        ..Options::default()
+    };
+    let quacks = run(&path, KeyStrategy::PayloadPrefix(key), &options);
+    let ids = udp
+        .iter()
+        .map(|p| id::id_from_payload_prefix(&p[20..], key));
+    assert_same(quacks.last().unwrap(), &quack_of(ids));
+    fs::remove_file(path).unwrap();
+}